@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::export::{Grid, Style};
+
+/// Frames of a simulation, pushed one state at a time.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Recorder {
+    pub frames: Vec<Grid>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, frame: Grid) {
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Animated GIF looping forever, showing each frame for `delay`
+    /// hundredths of a second. Frames are drawn at the top left of a canvas
+    /// large enough for all of them. `None` if nothing was recorded.
+    pub fn to_gif(&self, style: &Style, delay: u16) -> Option<Vec<u8>> {
+        let frames = self
+            .frames
+            .iter()
+            .map(|frame| frame.pixels(style))
+            .collect::<Vec<(usize, usize, Vec<u8>)>>();
+        let width = frames.iter().map(|f| f.0).max()?;
+        let height = frames.iter().map(|f| f.1).max()?;
+
+        // Grids rarely use more than a handful of colours, which can then be
+        // kept exactly in a global palette
+        let mut colors = HashMap::new();
+        for (_, _, pixels) in &frames {
+            for rgb in pixels.chunks(3) {
+                let next = colors.len();
+                colors.entry([rgb[0], rgb[1], rgb[2]]).or_insert(next);
+            }
+        }
+        let indexed = colors.len() <= 256;
+        let mut palette = vec![0; colors.len() * 3];
+        for (rgb, &i) in &colors {
+            palette[i * 3..i * 3 + 3].copy_from_slice(rgb);
+        }
+
+        let mut gif = vec![];
+        let global = if indexed { palette.as_slice() } else { &[] };
+        let mut encoder = gif::Encoder::new(&mut gif, width as u16, height as u16, global).unwrap();
+        encoder.set_repeat(gif::Repeat::Infinite).unwrap();
+
+        for (w, h, pixels) in &frames {
+            let mut frame = match indexed {
+                true => {
+                    let indices = pixels
+                        .chunks(3)
+                        .map(|rgb| colors[&[rgb[0], rgb[1], rgb[2]]] as u8)
+                        .collect::<Vec<u8>>();
+                    gif::Frame::from_indexed_pixels(*w as u16, *h as u16, indices, None)
+                }
+                false => gif::Frame::from_rgb_speed(*w as u16, *h as u16, pixels, 10),
+            };
+            frame.delay = delay;
+            encoder.write_frame(&frame).unwrap();
+        }
+
+        drop(encoder);
+        Some(gif)
+    }
+}
+
+#[cfg(test)]
+mod test_animation {
+    use super::Recorder;
+    use crate::export::{Grid, Style};
+
+    #[test]
+    fn gif() {
+        let mut recorder = Recorder::new();
+        assert_eq!(recorder.to_gif(&Style::default(), 10), None);
+
+        recorder.push(Grid::bars(&[1, 2, 3], 4));
+        recorder.push(Grid::bars(&[3, 2, 1, 5], 4));
+        let gif = recorder.to_gif(&Style::default(), 10).unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+
+        let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (56, 32));
+
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            frames += 1;
+        }
+        assert_eq!(frames, 2);
+    }
+}
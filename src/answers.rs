@@ -0,0 +1,195 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::input::normalize;
+
+/// Known answer for one part of a day, tied to the input it was computed on.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Answer {
+    pub day: u32,
+    pub part: u32,
+    pub input_hash: u64,
+    pub answer: String,
+}
+
+/// Known answers, stored one per line as tab separated
+/// `day part input-hash answer`. Newlines in answers are written as `\n`
+/// and backslashes as `\\`, lines starting with `#` are comments.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AnswerStore {
+    pub answers: Vec<Answer>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Mismatch { expected: String, actual: String },
+    Unknown,
+}
+
+impl AnswerStore {
+    pub fn get(&self, day: u32, part: u32, input_hash: u64) -> Option<&Answer> {
+        self.answers
+            .iter()
+            .find(|a| a.day == day && a.part == part && a.input_hash == input_hash)
+    }
+
+    /// Stores `answer`, replacing a previous one for the same day, part and
+    /// input.
+    pub fn record(&mut self, answer: Answer) {
+        match self.answers.iter_mut().find(|a| {
+            a.day == answer.day && a.part == answer.part && a.input_hash == answer.input_hash
+        }) {
+            Some(existing) => *existing = answer,
+            None => self.answers.push(answer),
+        }
+        self.answers.sort_by_key(|a| (a.day, a.part, a.input_hash));
+    }
+
+    pub fn verify(&self, day: u32, part: u32, input_hash: u64, actual: &str) -> Verdict {
+        match self.get(day, part, input_hash) {
+            None => Verdict::Unknown,
+            Some(known) if known.answer == actual => Verdict::Pass,
+            Some(known) => Verdict::Mismatch {
+                expected: known.answer.clone(),
+                actual: actual.to_string(),
+            },
+        }
+    }
+}
+
+impl FromStr for AnswerStore {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut store = AnswerStore::default();
+
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || format!("invalid answer on line {}: '{}'", i + 1, line);
+            let fields = line.splitn(4, '\t').collect::<Vec<&str>>();
+            if let [day, part, input_hash, answer] = fields[..] {
+                store.answers.push(Answer {
+                    day: day.parse().map_err(|_| invalid())?,
+                    part: part.parse().map_err(|_| invalid())?,
+                    input_hash: u64::from_str_radix(input_hash, 16).map_err(|_| invalid())?,
+                    answer: unescape(answer),
+                });
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        Ok(store)
+    }
+}
+
+impl Display for AnswerStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# day\tpart\tinput hash\tanswer")?;
+        for a in &self.answers {
+            writeln!(
+                f,
+                "{}\t{}\t{:016x}\t{}",
+                a.day,
+                a.part,
+                a.input_hash,
+                escape(&a.answer)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// FNV-1a hash of the normalised input, stable across platforms and Rust
+/// versions. Line endings and trailing whitespace don't change the hash.
+pub fn hash_input(input: &str) -> u64 {
+    normalize(input)
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::new();
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test_answers {
+    use super::{hash_input, Answer, AnswerStore, Verdict};
+
+    #[test]
+    fn round_trip() {
+        let mut store = AnswerStore::default();
+        store.record(Answer {
+            day: 13,
+            part: 2,
+            input_hash: hash_input("6,10"),
+            answer: "\n# #\n\\#\n".to_string(),
+        });
+        store.record(Answer {
+            day: 1,
+            part: 1,
+            input_hash: 42,
+            answer: "7".to_string(),
+        });
+
+        let parsed = store.to_string().parse::<AnswerStore>().unwrap();
+        assert_eq!(parsed, store);
+        assert_eq!(parsed.answers[0].day, 1);
+        assert!("1\t1\tnot-hex\t7".parse::<AnswerStore>().is_err());
+    }
+
+    #[test]
+    fn normalised_hash() {
+        assert_eq!(hash_input("\u{feff}1\r\n2 \r\n\r\n"), hash_input("1\n2"));
+        assert_ne!(hash_input("1\n2"), hash_input("1\n3"));
+    }
+
+    #[test]
+    fn verify() {
+        let mut store = AnswerStore::default();
+        store.record(Answer {
+            day: 1,
+            part: 1,
+            input_hash: 42,
+            answer: "7".to_string(),
+        });
+
+        assert_eq!(store.verify(1, 1, 42, "7"), Verdict::Pass);
+        assert_eq!(
+            store.verify(1, 1, 42, "8"),
+            Verdict::Mismatch {
+                expected: "7".to_string(),
+                actual: "8".to_string()
+            }
+        );
+        assert_eq!(store.verify(1, 1, 43, "7"), Verdict::Unknown);
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::solution::Day;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Phase {
+    Generator,
+    Part1,
+    Part2,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Phase::Generator => "generator",
+            Phase::Part1 => "part1",
+            Phase::Part2 => "part2",
+        })
+    }
+}
+
+impl FromStr for Phase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "generator" => Ok(Phase::Generator),
+            "part1" => Ok(Phase::Part1),
+            "part2" => Ok(Phase::Part2),
+            _ => Err(format!("invalid phase '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &mut [Duration]) -> Option<Self> {
+        samples.sort_unstable();
+
+        Some(Self {
+            min: *samples.first()?,
+            median: samples[samples.len() / 2],
+            max: *samples.last()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Measurement {
+    pub day: u32,
+    /// Name of the input, e.g. `example` or `synthetic`
+    pub input: String,
+    pub phase: Phase,
+    pub stats: Stats,
+}
+
+/// Times the generator and both parts of `day` separately, running each
+/// `iterations` times. The parts always run on a single parsed input.
+pub fn bench(day: &Day, input_name: &str, input: &str, iterations: usize) -> Vec<Measurement> {
+    let iterations = iterations.max(1);
+    let measure = |phase, f: &dyn Fn()| {
+        let mut samples = (0..iterations)
+            .map(|_| {
+                let start = Instant::now();
+                f();
+                start.elapsed()
+            })
+            .collect::<Vec<Duration>>();
+
+        Measurement {
+            day: day.day,
+            input: input_name.to_string(),
+            phase,
+            stats: Stats::from_samples(&mut samples).unwrap(),
+        }
+    };
+
+    let parsed = (day.parse)(input);
+
+    vec![
+        measure(Phase::Generator, &|| drop((day.parse)(input))),
+        measure(Phase::Part1, &|| drop((day.part1)(parsed.as_ref()))),
+        measure(Phase::Part2, &|| drop((day.part2)(parsed.as_ref()))),
+    ]
+}
+
+/// Saved measurements, one per line as tab separated
+/// `day input phase min median max` with durations in nanoseconds.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Baseline {
+    pub measurements: Vec<Measurement>,
+}
+
+impl Baseline {
+    /// Relative change of the median of `measurement` against the matching
+    /// baseline entry, e.g. `0.1` for 10% slower.
+    pub fn change(&self, measurement: &Measurement) -> Option<f64> {
+        let baseline = self.measurements.iter().find(|m| {
+            m.day == measurement.day && m.input == measurement.input && m.phase == measurement.phase
+        })?;
+
+        let before = baseline.stats.median.as_secs_f64();
+        let after = measurement.stats.median.as_secs_f64();
+        match before > 0.0 {
+            true => Some(after / before - 1.0),
+            false => None,
+        }
+    }
+}
+
+impl Display for Baseline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# day\tinput\tphase\tmin\tmedian\tmax")?;
+        for m in &self.measurements {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                m.day,
+                m.input,
+                m.phase,
+                m.stats.min.as_nanos(),
+                m.stats.median.as_nanos(),
+                m.stats.max.as_nanos()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Baseline {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut measurements = vec![];
+
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || format!("invalid measurement on line {}: '{}'", i + 1, line);
+            let nanos = |s: &str| s.parse().map(Duration::from_nanos).map_err(|_| invalid());
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            if let [day, input, phase, min, median, max] = fields[..] {
+                measurements.push(Measurement {
+                    day: day.parse().map_err(|_| invalid())?,
+                    input: input.to_string(),
+                    phase: phase.parse().map_err(|_| invalid())?,
+                    stats: Stats {
+                        min: nanos(min)?,
+                        median: nanos(median)?,
+                        max: nanos(max)?,
+                    },
+                });
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Self { measurements })
+    }
+}
+
+#[cfg(test)]
+mod test_bench {
+    use super::{bench, Baseline, Phase, Stats};
+    use crate::solution::find;
+    use std::time::Duration;
+
+    #[test]
+    fn stats() {
+        let mut samples = [5, 1, 4, 2, 3].map(Duration::from_millis);
+        let stats = Stats::from_samples(&mut samples).unwrap();
+
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.max, Duration::from_millis(5));
+        assert_eq!(Stats::from_samples(&mut []), None);
+    }
+
+    #[test]
+    fn baseline() {
+        let day = find(6).unwrap();
+        let measurements = bench(day, "example", day.example, 3);
+        assert_eq!(
+            measurements.iter().map(|m| m.phase).collect::<Vec<_>>(),
+            vec![Phase::Generator, Phase::Part1, Phase::Part2]
+        );
+
+        let baseline = Baseline { measurements };
+        let parsed = baseline.to_string().parse::<Baseline>().unwrap();
+        assert_eq!(parsed, baseline);
+
+        let mut slower = parsed.measurements[0].clone();
+        slower.stats.median *= 2;
+        let change = baseline.change(&slower).unwrap();
+        assert!((change - 1.0).abs() < 1e-9 || baseline.measurements[0].stats.median.is_zero());
+    }
+}
@@ -0,0 +1,651 @@
+use std::any::Any;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+use aoc_2021::animation::Recorder;
+use aoc_2021::answers::{hash_input, Answer, AnswerStore, Verdict};
+use aoc_2021::bench::{self, Baseline, Measurement};
+use aoc_2021::export::{self, ColorMap, Format, Style};
+use aoc_2021::report::{InputStats, PartReport, Report};
+use aoc_2021::simulation::Timeline;
+use aoc_2021::solution::{self, DAYS};
+use aoc_2021::tui::{self, App};
+
+const USAGE: &str =
+    "Usage: aoc <day|all> [part] [--input <file>|-] [--inputs <dir>] [--json <dir>|-]
+       aoc verify [day|all] [--inputs <dir>] [--answers <file>]
+       aoc record [day|all] [--inputs <dir>] [--answers <file>]
+       aoc bench [day|all] [--iterations <n>] [--scale <n>] [--seed <n>]
+                 [--baseline <file>] [--save-baseline <file>]
+       aoc generate <day> [--scale <n>] [--seed <n>]
+       aoc export [day|all] [--output <dir>] [--format png|ppm|svg]
+                  [--colors viridis|heat|gray] [--cell-size <n>]
+       aoc animate [day|all] [--output <dir>] [--delay <n>]
+                   [--colors viridis|heat|gray] [--cell-size <n>]
+       aoc step <day> [--input <file>] [--delay <n>] [--colors viridis|heat|gray]
+
+Runs the solutions without cargo-aoc. Inputs default to <dir>/day<N>.txt,
+with <dir> being input/2021 like cargo-aoc uses. Pass `-` as input to read
+from stdin. With --json, a JSON report with the answers, timings, input
+statistics and day specific diagnostics is written to <dir>/day<N>.json,
+or printed as one line per day instead of the answers for `-`.
+
+`verify` compares the answers for the local inputs with the ones kept in
+the answers file (<dir>/answers.txt by default), `record` stores them.
+
+`bench` times the generator and each part on the example, on a synthetic
+input of the given scale and on the local input if there is one. Timings
+are compared against the baseline file if given.
+
+`generate` prints a synthetic input, the same seed giving the same input.
+
+`export` renders the states of the grid based days as images, written to
+<dir>/day<N>-<frame>.<format> (export and png by default).
+
+`animate` records the simulation of the days that have one into an animated
+GIF, <dir>/day<N>.gif, showing each state for <n> hundredths of a second
+(10 by default).
+
+`step` opens a terminal UI to step forwards and backwards through the
+simulation of day 11, 13 or 14, playing it at <n> hundredths of a second
+per step.";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Mode {
+    Run,
+    Verify,
+    Record,
+    Bench,
+    Generate,
+    Export,
+    Animate,
+    Step,
+}
+
+struct Options {
+    mode: Mode,
+    days: Vec<u32>,
+    parts: Vec<u32>,
+    input: Option<String>,
+    inputs: PathBuf,
+    answers: PathBuf,
+    iterations: usize,
+    scale: usize,
+    seed: u64,
+    baseline: Option<PathBuf>,
+    save_baseline: Option<PathBuf>,
+    json: Option<String>,
+    output: PathBuf,
+    format: Format,
+    style: Style,
+    delay: u16,
+}
+
+struct PartResult {
+    part: u32,
+    answer: String,
+    duration: Duration,
+}
+
+struct DayResult {
+    parsed: Box<dyn Any>,
+    generator: Duration,
+    parts: Vec<PartResult>,
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            exit(2);
+        }
+    };
+
+    let succeeded = match options.mode {
+        Mode::Run => run(&options),
+        Mode::Verify | Mode::Record => check_answers(&options),
+        Mode::Bench => run_benchmarks(&options),
+        Mode::Generate => generate(&options),
+        Mode::Export => export_frames(&options),
+        Mode::Animate => animate(&options),
+        Mode::Step => step(&options),
+    };
+
+    if !succeeded {
+        exit(1);
+    }
+}
+
+fn run(options: &Options) -> bool {
+    let mut succeeded = true;
+
+    for &day in &options.days {
+        let result = read_input(day, options).and_then(|input| {
+            let input = input.trim_end().to_string();
+            let result = run_day(day, &options.parts, &input)?;
+            match &options.json {
+                Some(json) => write_report(day, json, &input, &result).map(|_| result),
+                None => Ok(result),
+            }
+        });
+
+        match result {
+            Ok(_) if options.json.as_deref() == Some("-") => {}
+            Ok(result) => {
+                println!("Day {:02} - generator: {:?}", day, result.generator);
+                for part in result.parts {
+                    println!(
+                        "Day {:02} - Part {}: {} ({:?})",
+                        day, part.part, part.answer, part.duration
+                    );
+                }
+            }
+            Err(error) => {
+                eprintln!("Day {:02}: {}", day, error);
+                succeeded = false;
+            }
+        }
+    }
+
+    succeeded
+}
+
+/// Writes the report to `<dir>/day<N>.json`, or prints it if `dir` is `-`.
+fn write_report(day: u32, dir: &str, input: &str, result: &DayResult) -> Result<(), String> {
+    let solution = solution::find(day).unwrap();
+    let diagnostics = catch(|| (solution.diagnostics)(result.parsed.as_ref()))
+        .map_err(|e| format!("diagnostics failed: {}", e))?;
+
+    let report = Report {
+        day,
+        input: InputStats::of(input),
+        generator_nanos: result.generator.as_nanos() as u64,
+        parts: result
+            .parts
+            .iter()
+            .map(|p| PartReport::new(p.part, p.answer.clone(), p.duration))
+            .collect(),
+        diagnostics,
+    };
+
+    if dir == "-" {
+        println!("{}", report.to_json());
+        return Ok(());
+    }
+
+    let path = PathBuf::from(dir).join(format!("day{}.json", day));
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&path, report.to_json_pretty()))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Verifies or records the answers for every day with a local input. Days
+/// without an input are skipped.
+fn check_answers(options: &Options) -> bool {
+    let mut store = match std::fs::read_to_string(&options.answers) {
+        Ok(content) => match content.parse::<AnswerStore>() {
+            Ok(store) => store,
+            Err(error) => {
+                eprintln!("{}: {}", options.answers.display(), error);
+                return false;
+            }
+        },
+        Err(_) if options.mode == Mode::Record => AnswerStore::default(),
+        Err(error) => {
+            eprintln!("{}: {}", options.answers.display(), error);
+            return false;
+        }
+    };
+
+    let mut succeeded = true;
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+
+    for &day in &options.days {
+        let input = match read_input(day, options) {
+            Ok(input) => input,
+            Err(_) => {
+                println!("Day {:02} - skipped, no input", day);
+                continue;
+            }
+        };
+        let input = input.trim_end();
+        let input_hash = hash_input(input);
+
+        let result = match run_day(day, &options.parts, input) {
+            Ok(result) => result,
+            Err(error) => {
+                println!("Day {:02} - FAIL: {}", day, error);
+                failed += options.parts.len();
+                succeeded = false;
+                continue;
+            }
+        };
+
+        for part in result.parts {
+            if options.mode == Mode::Record {
+                println!("Day {:02} - Part {}: recorded", day, part.part);
+                store.record(Answer {
+                    day,
+                    part: part.part,
+                    input_hash,
+                    answer: part.answer,
+                });
+                continue;
+            }
+
+            match store.verify(day, part.part, input_hash, &part.answer) {
+                Verdict::Pass => {
+                    println!("Day {:02} - Part {}: pass", day, part.part);
+                    passed += 1;
+                }
+                Verdict::Mismatch { expected, actual } => {
+                    println!(
+                        "Day {:02} - Part {}: MISMATCH, expected {} but got {}",
+                        day, part.part, expected, actual
+                    );
+                    failed += 1;
+                    succeeded = false;
+                }
+                Verdict::Unknown => {
+                    println!("Day {:02} - Part {}: no known answer", day, part.part);
+                    unknown += 1;
+                }
+            }
+        }
+    }
+
+    if options.mode == Mode::Record {
+        if let Err(error) = std::fs::write(&options.answers, store.to_string()) {
+            eprintln!("{}: {}", options.answers.display(), error);
+            return false;
+        }
+    } else {
+        println!(
+            "{} passed, {} failed, {} without known answer",
+            passed, failed, unknown
+        );
+    }
+
+    succeeded
+}
+
+fn run_benchmarks(options: &Options) -> bool {
+    let baseline = match &options.baseline {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| content.parse::<Baseline>())
+        {
+            Ok(baseline) => Some(baseline),
+            Err(error) => {
+                eprintln!("{}: {}", path.display(), error);
+                return false;
+            }
+        },
+        None => None,
+    };
+
+    let mut succeeded = true;
+    let mut measurements = vec![];
+
+    for &day in &options.days {
+        let day = solution::find(day).unwrap();
+
+        let mut inputs = vec![("example", day.example.to_string())];
+        if let Some(input) = (day.synthetic)(options.seed, options.scale) {
+            inputs.push(("synthetic", input));
+        }
+        if let Ok(input) = read_input(day.day, options) {
+            inputs.push(("input", input.trim_end().to_string()));
+        }
+
+        for (name, input) in inputs {
+            match catch(|| bench::bench(day, name, &input, options.iterations)) {
+                Ok(results) => {
+                    for measurement in results {
+                        print_measurement(&measurement, baseline.as_ref());
+                        measurements.push(measurement);
+                    }
+                }
+                Err(error) => {
+                    eprintln!("Day {:02} - {}: {}", day.day, name, error);
+                    succeeded = false;
+                }
+            }
+        }
+    }
+
+    if let Some(path) = &options.save_baseline {
+        let baseline = Baseline { measurements };
+        if let Err(error) = std::fs::write(path, baseline.to_string()) {
+            eprintln!("{}: {}", path.display(), error);
+            return false;
+        }
+    }
+
+    succeeded
+}
+
+fn generate(options: &Options) -> bool {
+    let mut succeeded = true;
+
+    for &day in &options.days {
+        let day = solution::find(day).unwrap();
+        match (day.synthetic)(options.seed, options.scale) {
+            Some(input) => println!("{}", input),
+            None => {
+                eprintln!("Day {:02}: no synthetic input", day.day);
+                succeeded = false;
+            }
+        }
+    }
+
+    succeeded
+}
+
+fn export_frames(options: &Options) -> bool {
+    let mut succeeded = true;
+
+    for &day in &options.days {
+        // Days that never have frames are skipped without looking for input
+        let solution = solution::find(day).unwrap();
+        if !solution.has_frames {
+            continue;
+        }
+
+        let result = read_input(day, options).and_then(|input| {
+            let frames = catch(|| (solution.frames)((solution.parse)(input.trim_end()).as_ref()))?;
+
+            std::fs::create_dir_all(&options.output)
+                .map_err(|e| format!("{}: {}", options.output.display(), e))?;
+            for (name, grid) in &frames {
+                let file = format!("day{}-{}.{}", day, name, options.format.extension());
+                export::write(grid, &options.style, &options.output.join(file))?;
+            }
+            Ok(frames.len())
+        });
+
+        match result {
+            Ok(frames) => println!("Day {:02} - {} frames exported", day, frames),
+            Err(error) => {
+                eprintln!("Day {:02}: {}", day, error);
+                succeeded = false;
+            }
+        }
+    }
+
+    succeeded
+}
+
+fn animate(options: &Options) -> bool {
+    let mut succeeded = true;
+
+    for &day in &options.days {
+        let solution = solution::find(day).unwrap();
+        if !solution.animated {
+            continue;
+        }
+
+        let result = read_input(day, options).and_then(|input| {
+            let recorder = catch(|| {
+                let mut recorder = Recorder::new();
+                (solution.animate)((solution.parse)(input.trim_end()).as_ref(), &mut recorder);
+                recorder
+            })?;
+            let gif = recorder.to_gif(&options.style, options.delay).unwrap();
+
+            std::fs::create_dir_all(&options.output)
+                .map_err(|e| format!("{}: {}", options.output.display(), e))?;
+            let path = options.output.join(format!("day{}.gif", day));
+            std::fs::write(&path, gif).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok(recorder.len())
+        });
+
+        match result {
+            Ok(frames) => println!("Day {:02} - {} frames animated", day, frames),
+            Err(error) => {
+                eprintln!("Day {:02}: {}", day, error);
+                succeeded = false;
+            }
+        }
+    }
+
+    succeeded
+}
+
+fn step(options: &Options) -> bool {
+    let day = options.days[0];
+    let solution = solution::find(day).unwrap();
+
+    let result = read_input(day, options).and_then(|input| {
+        let simulation =
+            catch(|| (solution.simulation)((solution.parse)(input.trim_end()).as_ref()))?
+                .ok_or("no simulation to step through")?;
+        let app = App::new(
+            format!("Day {:02}", day),
+            Timeline::new(simulation),
+            options.style,
+            Duration::from_millis(options.delay as u64 * 10),
+        );
+        tui::run(app).map_err(|e| format!("terminal UI failed: {}", e))
+    });
+
+    match result {
+        Ok(()) => true,
+        Err(error) => {
+            eprintln!("Day {:02}: {}", day, error);
+            false
+        }
+    }
+}
+
+fn print_measurement(measurement: &Measurement, baseline: Option<&Baseline>) {
+    let change = baseline
+        .and_then(|baseline| baseline.change(measurement))
+        .map(|change| format!(" ({:+.1}% vs baseline)", change * 100.0))
+        .unwrap_or_default();
+
+    println!(
+        "Day {:02} - {:<9} {:<9} min {:>10.1?}  median {:>10.1?}  max {:>10.1?}{}",
+        measurement.day,
+        measurement.input,
+        measurement.phase,
+        measurement.stats.min,
+        measurement.stats.median,
+        measurement.stats.max,
+        change
+    );
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut args = args.into_iter();
+    let mut positional = vec![];
+    let mut input = None;
+    let mut inputs = PathBuf::from("input/2021");
+    let mut answers = None;
+    let mut iterations = 10;
+    let mut scale = 10_000;
+    let mut seed = 2021;
+    let mut baseline = None;
+    let mut save_baseline = None;
+    let mut json = None;
+    let mut output = PathBuf::from("export");
+    let mut format = Format::Png;
+    let mut style = Style::default();
+    let mut delay = 10;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => input = Some(args.next().ok_or("--input needs a value")?),
+            "--inputs" => inputs = args.next().ok_or("--inputs needs a value")?.into(),
+            "--answers" => answers = Some(args.next().ok_or("--answers needs a value")?.into()),
+            "--iterations" => iterations = number(args.next(), "--iterations")?,
+            "--scale" => scale = number(args.next(), "--scale")?,
+            "--seed" => seed = number(args.next(), "--seed")?,
+            "--output" => output = args.next().ok_or("--output needs a value")?.into(),
+            "--format" => format = args.next().ok_or("--format needs a value")?.parse()?,
+            "--colors" => {
+                style.color_map = args
+                    .next()
+                    .ok_or("--colors needs a value")?
+                    .parse::<ColorMap>()?
+            }
+            "--cell-size" => style.cell_size = number(args.next(), "--cell-size")?,
+            "--delay" => delay = number(args.next(), "--delay")?,
+            "--json" => json = Some(args.next().ok_or("--json needs a value")?),
+            "--baseline" => baseline = Some(args.next().ok_or("--baseline needs a value")?.into()),
+            "--save-baseline" => {
+                save_baseline = Some(args.next().ok_or("--save-baseline needs a value")?.into())
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let mode = match positional.first().map(|m| m.as_str()) {
+        Some("verify") => Mode::Verify,
+        Some("record") => Mode::Record,
+        Some("bench") => Mode::Bench,
+        Some("generate") => Mode::Generate,
+        Some("export") => Mode::Export,
+        Some("animate") => Mode::Animate,
+        Some("step") => Mode::Step,
+        _ => Mode::Run,
+    };
+    if mode != Mode::Run {
+        positional.remove(0);
+        if positional.is_empty() {
+            positional.push("all".to_string());
+        }
+    }
+
+    let days = match positional.first().map(|d| d.as_str()) {
+        Some("all") => DAYS.iter().map(|d| d.day).collect(),
+        Some(day) => {
+            let day = day
+                .trim_start_matches("day")
+                .parse::<u32>()
+                .map_err(|_| format!("invalid day '{}'", day))?;
+            if solution::find(day).is_none() {
+                return Err(format!("day {} is not implemented", day));
+            }
+            vec![day]
+        }
+        None => return Err("missing day".to_string()),
+    };
+
+    let parts = match positional.get(1).map(|p| p.trim_start_matches("part")) {
+        None => vec![1, 2],
+        Some("1") => vec![1],
+        Some("2") => vec![2],
+        Some(part) => return Err(format!("invalid part '{}'", part)),
+    };
+
+    if mode == Mode::Generate && days.len() > 1 {
+        return Err("generate needs a single day".to_string());
+    }
+    if mode == Mode::Step && days.len() > 1 {
+        return Err("step needs a single day".to_string());
+    }
+    if input.is_some() && days.len() > 1 {
+        return Err("--input can only be used with a single day".to_string());
+    }
+
+    Ok(Options {
+        mode,
+        days,
+        parts,
+        input,
+        answers: answers.unwrap_or_else(|| inputs.join("answers.txt")),
+        inputs,
+        iterations,
+        scale,
+        seed,
+        baseline,
+        save_baseline,
+        json,
+        output,
+        format,
+        style,
+        delay,
+    })
+}
+
+fn number<T: std::str::FromStr>(value: Option<String>, flag: &str) -> Result<T, String> {
+    let value = value.ok_or(format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, flag))
+}
+
+fn read_input(day: u32, options: &Options) -> Result<String, String> {
+    match options.input.as_deref() {
+        Some("-") => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("failed to read stdin: {}", e))?;
+            Ok(input)
+        }
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            let path = options.inputs.join(format!("day{}.txt", day));
+            std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))
+        }
+    }
+}
+
+fn run_day(day: u32, parts: &[u32], input: &str) -> Result<DayResult, String> {
+    let day = solution::find(day).ok_or(format!("day {} is not implemented", day))?;
+
+    let start = Instant::now();
+    let parsed =
+        catch(|| (day.parse)(input)).map_err(|e| format!("failed to parse input: {}", e))?;
+    let generator = start.elapsed();
+
+    let mut results = vec![];
+    for &part in parts {
+        let start = Instant::now();
+        let answer = catch(|| day.solve(part, parsed.as_ref()))
+            .map_err(|e| format!("part {} failed: {}", part, e))?
+            .ok_or(format!("invalid part {}", part))?;
+
+        results.push(PartResult {
+            part,
+            answer,
+            duration: start.elapsed(),
+        });
+    }
+
+    Ok(DayResult {
+        parsed,
+        generator,
+        parts: results,
+    })
+}
+
+/// Runs `f`, turning a panic into an error with its message. Solutions report
+/// bad input by panicking, so the default hook is kept from printing those
+/// while `f` runs.
+fn catch<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    panic::set_hook(hook);
+
+    result.map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string())
+    })
+}
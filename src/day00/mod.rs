@@ -1,6 +1,10 @@
+use crate::input::normalize;
+use crate::solution::Solution;
+
 #[aoc_generator(day00)]
 pub fn input_generator(input: &str) -> Vec<()> {
-    vec![]
+    let input = normalize(input);
+    input.lines().map(|_| ()).collect()
 }
 
 #[aoc(day00, part1)]
@@ -13,21 +17,44 @@ pub fn solve_part2(input: &[()]) -> u32 {
     todo!("Solve part 2");
 }
 
+pub const EXAMPLE: &str = "";
+
+pub struct Day00;
+
+impl Solution for Day00 {
+    const DAY: u32 = 0;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Vec<()>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test_day00 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "";
+    use super::{input_generator, solve_part1, solve_part2, EXAMPLE};
 
     #[test]
     fn part1() {
-        let input = input_generator(INPUT);
+        let input = input_generator(EXAMPLE);
         assert_eq!(solve_part1(&input), 0);
     }
 
     #[test]
     fn part2() {
-        let input = input_generator(INPUT);
+        let input = input_generator(EXAMPLE);
         assert_eq!(solve_part2(&input), 0);
     }
-}
\ No newline at end of file
+}
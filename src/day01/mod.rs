@@ -1,7 +1,15 @@
+pub mod naive;
+
+use crate::input::normalize;
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::Rng;
+
 type InputType = u32;
 
 #[aoc_generator(day01)]
 pub fn input_generator(input: &str) -> Vec<InputType> {
+    let input = normalize(input);
     input
         .lines()
         .map(|l| l.parse())
@@ -10,30 +18,148 @@ pub fn input_generator(input: &str) -> Vec<InputType> {
 }
 
 #[aoc(day01, part1)]
-pub fn solve_part1(input: &[InputType]) -> u32 {
-    let mut counter = 0;
+pub fn solve_part1(input: &[InputType]) -> usize {
+    count_increases(input, 1)
+}
 
-    for i in 1..input.len() {
-        if input[i] > input[i - 1] {
-            counter += 1;
-        }
+#[aoc(day01, part2)]
+pub fn solve_part2(input: &[InputType]) -> usize {
+    count_increases(input, 3)
+}
+
+pub const EXAMPLE: &str = "199
+200
+208
+210
+200
+207
+240
+269
+260
+263";
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u32 = 1;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Vec<InputType>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
     }
 
-    counter
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let mut depth = 100u32;
+        let depths = (0..scale.max(1)).map(|_| {
+            depth = depth.saturating_add_signed(rng.gen_range(-10..=30));
+            depth
+        });
+        Some(itertools::join(depths, "\n"))
+    }
 }
 
-#[aoc(day01, part2)]
-pub fn solve_part2(input: &[InputType]) -> u32 {
-    let mut counter = 0;
+/// Number of times the sum of a `window` wide window is larger than the sum
+/// of the window one step before it. Both windows share all but their first
+/// and last value, so comparing those is enough.
+pub fn count_increases(input: &[InputType], window: usize) -> usize {
+    increases(input, window).count()
+}
+
+/// Start index of every window whose sum is larger than the one before it.
+pub fn increases(input: &[InputType], window: usize) -> impl Iterator<Item = usize> + '_ {
+    let pairs = match window {
+        0 => 0,
+        _ => input.len().saturating_sub(window),
+    };
 
-    for i in 0..input.len() - 3 {
-        let a = input[i] + input[i + 1] + input[i + 2];
-        let b = input[i + 1] + input[i + 2] + input[i + 3];
+    (0..pairs)
+        .filter(move |&i| input[i + window] > input[i])
+        .map(|i| i + 1)
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct WindowStats {
+    pub increases: Vec<usize>,
+    /// Most increases in a row
+    pub longest_run: usize,
+}
 
-        if b > a {
-            counter += 1;
+pub fn window_stats(input: &[InputType], window: usize) -> WindowStats {
+    let increases = increases(input, window).collect::<Vec<usize>>();
+
+    let mut longest_run = 0;
+    let mut run = 0;
+    for (i, index) in increases.iter().enumerate() {
+        match i > 0 && increases[i - 1] + 1 == *index {
+            true => run += 1,
+            false => run = 1,
         }
+        longest_run = longest_run.max(run);
+    }
+
+    WindowStats {
+        increases,
+        longest_run,
+    }
+}
+
+#[cfg(test)]
+mod test_day01 {
+    use super::{
+        count_increases, input_generator, naive, solve_part1, solve_part2, window_stats,
+        EXAMPLE as INPUT,
+    };
+    use proptest::prelude::*;
+
+    #[test]
+    fn part1() {
+        let input = input_generator(INPUT);
+        assert_eq!(solve_part1(&input), 7);
     }
 
-    counter
+    #[test]
+    fn part2() {
+        let input = input_generator(INPUT);
+        assert_eq!(solve_part2(&input), 5);
+    }
+
+    #[test]
+    fn windows() {
+        let input = input_generator(INPUT);
+
+        assert_eq!(count_increases(&input[..2], 3), 0);
+        assert_eq!(count_increases(&input, 10), 0);
+        assert_eq!(count_increases(&input, 0), 0);
+
+        let stats = window_stats(&input, 1);
+        assert_eq!(stats.increases, vec![1, 2, 3, 5, 6, 7, 9]);
+        assert_eq!(stats.longest_run, 3);
+
+        let stats = window_stats(&input, 3);
+        assert_eq!(stats.increases, vec![1, 4, 5, 6, 7]);
+        assert_eq!(stats.longest_run, 4);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(input in prop::collection::vec(0u32..10_000, 0..200), window in 0usize..6) {
+            prop_assert_eq!(
+                count_increases(&input, window),
+                naive::count_increases(&input, window)
+            );
+        }
+    }
 }
@@ -0,0 +1,16 @@
+//! Straightforward solution, summing every window.
+
+use super::InputType;
+
+pub fn count_increases(input: &[InputType], window: usize) -> usize {
+    if window == 0 {
+        return 0;
+    }
+
+    let sums = input
+        .windows(window)
+        .map(|w| w.iter().map(|&d| d as u64).sum())
+        .collect::<Vec<u64>>();
+
+    sums.windows(2).filter(|pair| pair[1] > pair[0]).count()
+}
@@ -1,67 +1,356 @@
+use crate::input::normalize;
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 type InputType = Command;
 
 #[aoc_generator(day02)]
 pub fn input_generator(input: &str) -> Vec<InputType> {
+    let input = normalize(input);
     input
         .lines()
-        .map(|l| {
-            let line: Vec<String> = l.split(' ').map(|c| c.to_string()).collect();
-            let cmd = line.get(0).unwrap();
-            let val: i32 = line.get(1).unwrap().parse()?;
-
-            match cmd.as_str() {
-                "forward" => Ok(Command::Forward(val)),
-                "down" => Ok(Command::Down(val)),
-                "up" => Ok(Command::Up(val)),
-                _ => panic!("Invalid input"),
-            }
-        })
-        .collect::<Result<Vec<InputType>, std::num::ParseIntError>>()
+        .map(|l| l.parse())
+        .collect::<Result<Vec<InputType>, CommandError>>()
         .unwrap()
 }
 
 #[aoc(day02, part1)]
 pub fn solve_part1(input: &[InputType]) -> i32 {
-    let mut h_pos = 0;
-    let mut depth = 0;
-
-    for cmd in input {
-        match cmd {
-            InputType::Forward(v) => h_pos += v,
-            InputType::Down(v) => depth += v,
-            InputType::Up(v) => depth -= v,
-        }
-    }
+    let mut submarine = RegistrySubmarine::simple();
+    submarine.run(input).unwrap();
 
-    h_pos * depth
+    submarine.position().product()
 }
 
 #[aoc(day02, part2)]
 pub fn solve_part2(input: &[InputType]) -> i32 {
-    let mut h_pos = 0;
-    let mut aim = 0;
-    let mut depth = 0;
-
-    for cmd in input {
-        match cmd {
-            InputType::Forward(v) => {
-                h_pos += v;
-                depth += aim * v;
-            }
-            InputType::Down(v) => {
-                aim += v;
-            }
-            InputType::Up(v) => {
-                aim -= v;
+    let mut submarine = RegistrySubmarine::aim();
+    submarine.run(input).unwrap();
+
+    submarine.position().product()
+}
+
+pub const EXAMPLE: &str = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u32 = 2;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Vec<InputType>;
+    type Output1 = i32;
+    type Output2 = i32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    /// Steers the aim towards keeping the depth close to zero, so the
+    /// products still fit in an `i32` for large inputs.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let (mut aim, mut depth) = (0, 0);
+        let commands = (0..scale.max(1)).map(|_| {
+            let value = rng.gen_range(1..=9);
+            if rng.gen_bool(0.5) {
+                depth += aim * value;
+                format!("forward {}", value)
+            } else if (depth > 0 || aim > 9) && aim > -10 {
+                aim -= value;
+                format!("up {}", value)
+            } else {
+                aim += value;
+                format!("down {}", value)
             }
+        });
+        Some(itertools::join(commands, "\n"))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Command {
+    pub name: String,
+    pub value: i32,
+}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s
+            .split_once(' ')
+            .ok_or_else(|| CommandError::Invalid(s.to_string()))?;
+        let value = value
+            .parse()
+            .map_err(|_| CommandError::Invalid(s.to_string()))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            value,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub struct Position {
+    pub horizontal: i32,
+    pub depth: i32,
+    pub aim: i32,
+}
+
+impl Position {
+    pub fn product(&self) -> i32 {
+        self.horizontal * self.depth
+    }
+}
+
+pub type Handler = fn(&mut Position, i32);
+
+/// Commands a submarine understands, by name.
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry(HashMap<String, Handler>);
+
+impl CommandRegistry {
+    pub fn register(&mut self, name: &str, handler: Handler) {
+        self.0.insert(name.to_string(), handler);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Handler> {
+        self.0.get(name).copied()
+    }
+
+    /// Commands from part 1, where `down` and `up` change the depth directly.
+    pub fn simple() -> Self {
+        let mut registry = Self::default();
+        registry.register("forward", |p, v| p.horizontal += v);
+        registry.register("back", |p, v| p.horizontal -= v);
+        registry.register("down", |p, v| p.depth += v);
+        registry.register("up", |p, v| p.depth -= v);
+        registry.register("reset", |p, _| *p = Position::default());
+        registry
+    }
+
+    /// Commands from part 2, where `down` and `up` change the aim and moving
+    /// changes the depth along it.
+    pub fn aim() -> Self {
+        let mut registry = Self::default();
+        registry.register("forward", |p, v| {
+            p.horizontal += v;
+            p.depth += p.aim * v;
+        });
+        registry.register("back", |p, v| {
+            p.horizontal -= v;
+            p.depth -= p.aim * v;
+        });
+        registry.register("down", |p, v| p.aim += v);
+        registry.register("up", |p, v| p.aim -= v);
+        registry.register("aim", |p, v| p.aim = v);
+        registry.register("reset", |p, _| *p = Position::default());
+        registry
+    }
+}
+
+/// State machine driven by named commands. Implementors only provide their
+/// state and commands, executing and tracing comes for free.
+pub trait Submarine {
+    fn registry(&self) -> &CommandRegistry;
+
+    fn registry_mut(&mut self) -> &mut CommandRegistry;
+
+    fn position(&self) -> Position;
+
+    fn position_mut(&mut self) -> &mut Position;
+
+    fn execute(&mut self, command: &Command) -> Result<Position, CommandError> {
+        let handler = self
+            .registry()
+            .get(&command.name)
+            .ok_or_else(|| CommandError::Unknown(command.name.clone()))?;
+        handler(self.position_mut(), command.value);
+
+        Ok(self.position())
+    }
+
+    /// Executes all commands and returns every position the submarine went
+    /// through, starting with the one before the first command.
+    fn run(&mut self, commands: &[Command]) -> Result<Vec<Position>, CommandError> {
+        let mut trace = vec![self.position()];
+        for command in commands {
+            trace.push(self.execute(command)?);
+        }
+
+        Ok(trace)
+    }
+}
+
+/// Submarine whose behaviour is defined entirely by the commands in its
+/// registry. Parts 1 and 2 only differ in those commands.
+#[derive(Debug, Clone)]
+pub struct RegistrySubmarine {
+    registry: CommandRegistry,
+    position: Position,
+}
+
+impl RegistrySubmarine {
+    pub fn new(registry: CommandRegistry) -> Self {
+        Self {
+            registry,
+            position: Position::default(),
         }
     }
 
-    h_pos * depth
+    /// Submarine from part 1, see [`CommandRegistry::simple`].
+    pub fn simple() -> Self {
+        Self::new(CommandRegistry::simple())
+    }
+
+    /// Submarine from part 2, see [`CommandRegistry::aim`].
+    pub fn aim() -> Self {
+        Self::new(CommandRegistry::aim())
+    }
 }
 
-pub enum Command {
-    Forward(i32),
-    Down(i32),
-    Up(i32),
+impl Submarine for RegistrySubmarine {
+    fn registry(&self) -> &CommandRegistry {
+        &self.registry
+    }
+
+    fn registry_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.registry
+    }
+
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn position_mut(&mut self) -> &mut Position {
+        &mut self.position
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CommandError {
+    Invalid(String),
+    Unknown(String),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Invalid(line) => write!(f, "invalid command '{}'", line),
+            CommandError::Unknown(name) => write!(f, "unknown command '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+#[cfg(test)]
+mod test_day02 {
+    use super::{
+        input_generator, solve_part1, solve_part2, Command, CommandError, CommandRegistry,
+        Position, RegistrySubmarine, Submarine, EXAMPLE as INPUT,
+    };
+
+    #[test]
+    fn part1() {
+        let input = input_generator(INPUT);
+        assert_eq!(solve_part1(&input), 150);
+    }
+
+    #[test]
+    fn part2() {
+        let input = input_generator(INPUT);
+        assert_eq!(solve_part2(&input), 900);
+    }
+
+    #[test]
+    fn extended_commands() {
+        let input = input_generator("forward 5\naim 2\nback 1\ndive 3");
+        let mut submarine = RegistrySubmarine::aim();
+
+        assert_eq!(
+            submarine.run(&input),
+            Err(CommandError::Unknown("dive".to_string()))
+        );
+
+        let mut submarine = RegistrySubmarine::aim();
+        submarine
+            .registry_mut()
+            .register("dive", |p, v| p.depth += 2 * v);
+        let trace = submarine.run(&input).unwrap();
+        assert_eq!(trace.len(), 5);
+        assert_eq!(
+            trace[4],
+            Position {
+                horizontal: 4,
+                depth: 4,
+                aim: 2
+            }
+        );
+
+        let mut submarine = RegistrySubmarine::simple();
+        let trace = submarine.run(&input_generator("down 4\nreset 0")).unwrap();
+        assert_eq!(trace.last(), Some(&Position::default()));
+    }
+
+    /// Counts the commands it executes, on top of the part 2 commands.
+    struct Counting {
+        inner: RegistrySubmarine,
+        executed: usize,
+    }
+
+    impl Submarine for Counting {
+        fn registry(&self) -> &CommandRegistry {
+            self.inner.registry()
+        }
+
+        fn registry_mut(&mut self) -> &mut CommandRegistry {
+            self.inner.registry_mut()
+        }
+
+        fn position(&self) -> Position {
+            self.inner.position()
+        }
+
+        fn position_mut(&mut self) -> &mut Position {
+            self.inner.position_mut()
+        }
+
+        fn execute(&mut self, command: &Command) -> Result<Position, CommandError> {
+            self.executed += 1;
+            self.inner.execute(command)
+        }
+    }
+
+    #[test]
+    fn custom_submarine() {
+        let input = input_generator(INPUT);
+        let mut submarine = Counting {
+            inner: RegistrySubmarine::aim(),
+            executed: 0,
+        };
+
+        let trace = submarine.run(&input).unwrap();
+        assert_eq!(submarine.executed, 6);
+        assert_eq!(trace.last().unwrap().product(), 900);
+    }
 }
@@ -1,136 +1,311 @@
-type InputType = Vec<u8>;
+use crate::input::normalize;
+use crate::solution::Solution;
+use crate::synthetic;
+use num_bigint::BigUint;
+use rand::Rng;
+use std::fmt::{Display, Formatter};
 
 #[aoc_generator(day03)]
-pub fn input_generator(input: &str) -> Vec<InputType> {
-    input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_string().parse())
-                .collect::<Result<Vec<u8>, std::num::ParseIntError>>()
-        })
-        .collect::<Result<Vec<InputType>, std::num::ParseIntError>>()
-        .unwrap()
+pub fn input_generator(input: &str) -> Diagnostics {
+    let input = normalize(input);
+    input.parse().unwrap()
 }
 
+/// Products of two rates can be twice as wide as the lines, which no longer
+/// fits a `u128` for lines wider than 64 bits.
 #[aoc(day03, part1)]
-pub fn solve_part1(input: &[InputType]) -> u32 {
-    let mut gamma_rate = String::new();
-    let mut epsilon_rate = String::new();
-
-    for i in 0..input[0].len() {
-        let mut zero_count = 0;
-        let mut one_count = 0;
-
-        for x in input {
-            match x[i] {
-                0 => zero_count += 1,
-                1 => one_count += 1,
-                _ => panic!("Invalid Input"),
-            }
-        }
+pub fn solve_part1(input: &Diagnostics) -> BigUint {
+    BigUint::from(input.gamma_rate()) * input.epsilon_rate()
+}
 
-        if zero_count > one_count {
-            gamma_rate = format!("{}{}", gamma_rate, 0);
-            epsilon_rate = format!("{}{}", epsilon_rate, 1);
-        } else {
-            gamma_rate = format!("{}{}", gamma_rate, 1);
-            epsilon_rate = format!("{}{}", epsilon_rate, 0);
-        }
+#[aoc(day03, part2)]
+pub fn solve_part2(input: &Diagnostics) -> BigUint {
+    let ogr = input.oxygen_generator_rating().unwrap();
+    let csr = input.co2_scrubber_rating().unwrap();
+
+    BigUint::from(ogr) * csr
+}
+
+pub const EXAMPLE: &str = "00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010";
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u32 = 3;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Diagnostics;
+    type Output1 = BigUint;
+    type Output2 = BigUint;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
     }
 
-    let gamma_rate = u32::from_str_radix(&gamma_rate, 2).unwrap();
-    let epsilon_rate = u32::from_str_radix(&epsilon_rate, 2).unwrap();
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
 
-    gamma_rate * epsilon_rate
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let lines = (0..scale.max(1)).map(|_| format!("{:012b}", rng.gen_range(0..1 << 12)));
+        Some(itertools::join(lines, "\n"))
+    }
 }
 
-#[aoc(day03, part2)]
-pub fn solve_part2(input: &[InputType]) -> u32 {
-    let mut ogr_candidates: Vec<InputType> = input.to_vec();
-    let mut csr_candidates: Vec<InputType> = input.to_vec();
-
-    for i in 0..input[0].len() {
-        let most_common_ogr = most_common_bit(&ogr_candidates, i);
-        let most_common_csr = most_common_bit(&csr_candidates, i);
-
-        if ogr_candidates.len() > 1 {
-            ogr_candidates = ogr_candidates
-                .into_iter()
-                .filter(|x| x[i] == most_common_ogr.unwrap_or(1))
-                .collect::<Vec<InputType>>();
+/// Report lines packed into integers, most significant bit first, so lines
+/// of up to 128 bits are supported. `lines` is kept sorted.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostics {
+    pub width: usize,
+    pub lines: Vec<u128>,
+}
+
+impl Diagnostics {
+    pub fn new(width: usize, mut lines: Vec<u128>) -> Result<Self, DiagnosticError> {
+        if width == 0 || width > 128 {
+            return Err(DiagnosticError::InvalidWidth(width));
         }
+        if let Some(&line) = lines.iter().find(|&&l| l & !mask(width) != 0) {
+            return Err(DiagnosticError::TooWide(line));
+        }
+
+        lines.sort_unstable();
+        Ok(Self { width, lines })
+    }
+
+    /// Number of lines with the bit at `bit` set, counting from the least
+    /// significant bit.
+    pub fn ones(&self, bit: usize) -> usize {
+        self.lines.iter().filter(|&&l| l >> bit & 1 == 1).count()
+    }
+
+    pub fn gamma_rate(&self) -> u128 {
+        let total = self.lines.len();
+        (0..self.width)
+            .filter(|&bit| self.ones(bit) * 2 >= total)
+            .fold(0, |rate, bit| rate | 1 << bit)
+    }
+
+    pub fn epsilon_rate(&self) -> u128 {
+        !self.gamma_rate() & mask(self.width)
+    }
+
+    pub fn oxygen_generator_rating(&self) -> Result<u128, DiagnosticError> {
+        self.rating(BitCriteria::OXYGEN_GENERATOR)
+    }
+
+    pub fn co2_scrubber_rating(&self) -> Result<u128, DiagnosticError> {
+        self.rating(BitCriteria::CO2_SCRUBBER)
+    }
+
+    /// Descends the sorted lines like a binary trie: every prefix is a
+    /// contiguous range, which splits into its 0 and 1 children at a
+    /// partition point. `criteria` picks the child to follow at every bit.
+    pub fn rating(&self, criteria: BitCriteria) -> Result<u128, DiagnosticError> {
+        let (mut lo, mut hi) = (0, self.lines.len());
+
+        for bit in (0..self.width).rev() {
+            if hi - lo == 0 {
+                return Err(DiagnosticError::NoCandidates(bit + 1));
+            }
+            if criteria.stop_at_one && hi - lo == 1 {
+                break;
+            }
 
-        if csr_candidates.len() > 1 {
-            csr_candidates = csr_candidates
-                .into_iter()
-                .filter(|x| x[i] != most_common_csr.unwrap_or(1))
-                .collect::<Vec<InputType>>();
+            let mid = lo + self.lines[lo..hi].partition_point(|l| l >> bit & 1 == 0);
+            match criteria.select(mid - lo, hi - mid) {
+                Bit::One => lo = mid,
+                Bit::Zero => hi = mid,
+            }
+        }
+
+        match hi - lo {
+            0 => Err(DiagnosticError::NoCandidates(0)),
+            _ => Ok(self.lines[lo]),
         }
     }
+}
 
-    let ogr: String = ogr_candidates[0]
-        .iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>()
-        .join("");
-    let csr: String = csr_candidates[0]
-        .iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>()
-        .join("");
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Commonness {
+    Most,
+    Least,
+}
 
-    let ogr = u32::from_str_radix(&ogr, 2).unwrap();
-    let csr = u32::from_str_radix(&csr, 2).unwrap();
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Bit {
+    Zero,
+    One,
+}
 
-    ogr * csr
+/// Rule for filtering report lines bit by bit, keeping the lines whose bit
+/// is the most or least common one, or `tie_break` if both are as common.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BitCriteria {
+    pub keep: Commonness,
+    pub tie_break: Bit,
+    pub stop_at_one: bool,
 }
 
-fn most_common_bit(input: &[InputType], index: usize) -> Option<u8> {
-    let mut zero_count = 0;
-    let mut one_count = 0;
+impl BitCriteria {
+    pub const OXYGEN_GENERATOR: Self = Self {
+        keep: Commonness::Most,
+        tie_break: Bit::One,
+        stop_at_one: true,
+    };
 
-    for x in input {
-        match x[index] {
-            0 => zero_count += 1,
-            1 => one_count += 1,
-            _ => panic!("Invalid Input"),
+    pub const CO2_SCRUBBER: Self = Self {
+        keep: Commonness::Least,
+        tie_break: Bit::Zero,
+        stop_at_one: true,
+    };
+
+    /// The bit to keep, given how many candidates have a 0 and a 1.
+    pub fn select(&self, zeros: usize, ones: usize) -> Bit {
+        match (zeros.cmp(&ones), self.keep) {
+            (std::cmp::Ordering::Equal, _) => self.tie_break,
+            (std::cmp::Ordering::Less, Commonness::Most) => Bit::One,
+            (std::cmp::Ordering::Greater, Commonness::Least) => Bit::One,
+            _ => Bit::Zero,
         }
     }
+}
+
+impl std::str::FromStr for Diagnostics {
+    type Err = DiagnosticError;
 
-    match zero_count.cmp(&one_count) {
-        std::cmp::Ordering::Less => Some(1),
-        std::cmp::Ordering::Equal => None,
-        std::cmp::Ordering::Greater => Some(0),
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let width = s.lines().next().map(|l| l.len()).unwrap_or(0);
+
+        let lines = s
+            .lines()
+            .map(|l| {
+                if l.len() != width {
+                    return Err(DiagnosticError::UnevenLine(l.to_string()));
+                }
+                l.chars().try_fold(0u128, |acc, c| match c {
+                    '0' => Ok(acc << 1),
+                    '1' => Ok(acc << 1 | 1),
+                    _ => Err(DiagnosticError::InvalidDigit(c)),
+                })
+            })
+            .collect::<Result<Vec<u128>, DiagnosticError>>()?;
+
+        Self::new(width, lines)
     }
 }
 
+fn mask(width: usize) -> u128 {
+    match width {
+        128 => u128::MAX,
+        _ => (1 << width) - 1,
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DiagnosticError {
+    InvalidWidth(usize),
+    InvalidDigit(char),
+    NoCandidates(usize),
+    UnevenLine(String),
+    TooWide(u128),
+}
+
+impl Display for DiagnosticError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticError::InvalidWidth(w) => write!(f, "line width {} is not in 1..=128", w),
+            DiagnosticError::InvalidDigit(c) => write!(f, "invalid digit '{}'", c),
+            DiagnosticError::NoCandidates(bit) => {
+                write!(f, "no candidates left after filtering bit {}", bit)
+            }
+            DiagnosticError::UnevenLine(l) => write!(f, "line '{}' differs in width", l),
+            DiagnosticError::TooWide(l) => write!(f, "line {:b} is wider than the report", l),
+        }
+    }
+}
+
+impl std::error::Error for DiagnosticError {}
+
 #[cfg(test)]
 mod test_day03 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "00100
-11110
-10110
-10111
-10101
-01111
-00111
-11100
-10000
-11001
-00010
-01010";
+    use super::{
+        input_generator, solve_part1, solve_part2, Bit, BitCriteria, Commonness, DiagnosticError,
+        EXAMPLE as INPUT,
+    };
+    use num_bigint::BigUint;
 
     #[test]
     fn part1() {
         let input = input_generator(INPUT);
-        assert_eq!(solve_part1(&input), 198);
+        assert_eq!(solve_part1(&input), BigUint::from(198u32));
     }
 
     #[test]
     fn part2() {
         let input = input_generator(INPUT);
-        assert_eq!(solve_part2(&input), 230);
+        assert_eq!(solve_part2(&input), BigUint::from(230u32));
+    }
+
+    #[test]
+    fn wide_lines() {
+        let mut input = String::new();
+        for line in INPUT.lines() {
+            input.push_str(&line.repeat(20));
+            input.push('\n');
+        }
+        let input = input_generator(&input);
+
+        assert_eq!(input.width, 100);
+        assert_eq!(input.gamma_rate().count_ones(), 20 * 22u32.count_ones());
+        assert_eq!(input.oxygen_generator_rating().unwrap() >> 95, 0b10111);
+
+        let (gamma, epsilon) = (input.gamma_rate(), input.epsilon_rate());
+        let part1 = solve_part1(&input);
+        assert!(part1.bits() > 128);
+        assert_eq!(&part1 / gamma, BigUint::from(epsilon));
+        assert_eq!(
+            part1.iter_u64_digits().next(),
+            Some(gamma.wrapping_mul(epsilon) as u64)
+        );
+        assert!(solve_part2(&input).bits() > 128);
+    }
+
+    #[test]
+    fn criteria() {
+        let input = input_generator(INPUT);
+
+        let least_common_ones = BitCriteria {
+            keep: Commonness::Least,
+            tie_break: Bit::One,
+            stop_at_one: true,
+        };
+        assert_eq!(input.rating(least_common_ones), Ok(0b01111));
+
+        // Filtering on past the last candidate drops it at the first 0
+        let exhaustive = BitCriteria {
+            stop_at_one: false,
+            ..BitCriteria::CO2_SCRUBBER
+        };
+        assert_eq!(
+            input.rating(exhaustive),
+            Err(DiagnosticError::NoCandidates(1))
+        );
     }
 }
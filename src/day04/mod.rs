@@ -1,11 +1,20 @@
-use std::num::ParseIntError;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::num::{NonZeroUsize, ParseIntError};
 
+use crate::input::{normalize, sections};
+use crate::solution::Solution;
+use crate::synthetic;
 use nalgebra::DMatrix;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 
 #[aoc_generator(day04)]
 pub fn input_generator(input: &str) -> BingoGame {
-    let mut sections = input
-        .split("\n\n")
+    let input = normalize(input);
+    let mut sections = sections(&input)
+        .into_iter()
         .map(|x| x.to_string())
         .collect::<Vec<String>>();
 
@@ -40,112 +49,395 @@ pub fn parse_board(board_input: &str) -> BingoBoard {
         })
         .collect::<Vec<Vec<BoardField>>>();
 
-    let size = board.len();
+    let rows = board.len();
+    let columns = board.first().map(|r| r.len()).unwrap_or(0);
+    if board.iter().any(|r| r.len() != columns) {
+        panic!("Invalid input: rows of different length");
+    }
     let flatten = board.into_iter().flatten().collect::<Vec<BoardField>>();
 
-    let dm = DMatrix::from_iterator(size, size, flatten);
-
-    BingoBoard(dm)
+    BingoBoard::new(DMatrix::from_row_slice(rows, columns, &flatten))
 }
 
 #[aoc(day04, part1)]
 pub fn solve_part1(input: &BingoGame) -> u32 {
-    let mut game = input.clone();
+    input.play().first().map(|win| win.score).unwrap_or(0)
+}
 
-    for command in game.commands {
-        // Check fields
-        for board in &mut game.boards {
-            board.check_value(command);
+#[aoc(day04, part2)]
+pub fn solve_part2(input: &BingoGame) -> u32 {
+    input.play().last().map(|win| win.score).unwrap_or(0)
+}
 
-            // Check if won
-            if board.has_won() {
-                let score = board.get_score();
+pub const EXAMPLE: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
 
-                return score * command;
-            }
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u32 = 4;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = BingoGame;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    /// Draws all numbers from 0 to 99, with `scale` 5x5 boards.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let mut numbers = (0..100).collect::<Vec<u32>>();
+
+        numbers.shuffle(&mut rng);
+        let mut sections = vec![itertools::join(&numbers, ",")];
+        for _ in 0..scale.max(1) {
+            let board = numbers.choose_multiple(&mut rng, 25).collect::<Vec<_>>();
+            let rows = board
+                .chunks(5)
+                .map(|row| itertools::join(row.iter().map(|n| format!("{:>2}", n)), " "));
+            sections.push(itertools::join(rows, "\n"));
         }
+
+        Some(sections.join("\n\n"))
     }
+}
 
-    0
+#[derive(Debug, Clone)]
+pub struct BingoGame {
+    pub commands: Vec<u32>,
+    pub boards: Vec<BingoBoard>,
 }
 
-#[aoc(day04, part2)]
-pub fn solve_part2(input: &BingoGame) -> u32 {
-    let mut game = input.clone();
-    let mut winning_boards: Vec<usize> = vec![];
-    let mut winning_boards_with_command: Vec<(usize, u32)> = vec![];
+impl BingoGame {
+    /// Same game, with every board won by any of `patterns` instead.
+    pub fn with_patterns(&self, patterns: &[WinPattern]) -> Result<Self, PatternError> {
+        Ok(Self {
+            commands: self.commands.clone(),
+            boards: self
+                .boards
+                .iter()
+                .map(|b| BingoBoard::with_patterns(b.fields.clone(), patterns))
+                .collect::<Result<Vec<BingoBoard>, PatternError>>()?,
+        })
+    }
+
+    /// Plays every call and returns the wins in the order they happened.
+    /// Boards stop taking part once they have won, boards that never win are
+    /// missing from the timeline.
+    pub fn play(&self) -> Vec<Win> {
+        let mut boards = self.boards.clone();
+        let mut has_won = vec![false; boards.len()];
+        let mut timeline = vec![];
+
+        for (call_index, &call) in self.commands.iter().enumerate() {
+            for (board_index, board) in boards.iter_mut().enumerate() {
+                if has_won[board_index] || !board.check_value(call) {
+                    continue;
+                }
+
+                if board.has_won() {
+                    has_won[board_index] = true;
+                    timeline.push(Win {
+                        board_index,
+                        winning_call: call,
+                        score: board.get_score() * call,
+                        call_index,
+                    });
+                }
+            }
+        }
+
+        timeline
+    }
+
+    /// Plays `rounds` games with the calls shuffled by an RNG seeded with
+    /// `seed` and reports how each board fared across all of them.
+    pub fn analyse(&self, rounds: NonZeroUsize, seed: u64) -> Vec<BoardOdds> {
+        let rounds = rounds.get();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game = self.clone();
+
+        let mut wins = vec![0usize; self.boards.len()];
+        let mut call_index_sums = vec![0usize; self.boards.len()];
+        let mut last = vec![0usize; self.boards.len()];
 
-    for command in game.commands {
-        // Check fields
-        for board_index in 0..game.boards.len() {
-            let board = game.boards.get_mut(board_index).unwrap();
+        for _ in 0..rounds {
+            game.commands.shuffle(&mut rng);
+            let timeline = game.play();
 
-            if !winning_boards.contains(&board_index) {
-                board.check_value(command);
+            for win in &timeline {
+                wins[win.board_index] += 1;
+                call_index_sums[win.board_index] += win.call_index;
             }
 
-            // Check if won
-            if board.has_won() && !winning_boards.contains(&board_index) {
-                winning_boards.push(board_index);
-                winning_boards_with_command.push((board_index, command));
+            // Boards winning on the same call as the final winner share last place
+            if let Some(final_win) = timeline.last() {
+                timeline
+                    .iter()
+                    .filter(|w| w.call_index == final_win.call_index)
+                    .for_each(|w| last[w.board_index] += 1);
             }
         }
-    }
 
-    let (last_index, command) = *winning_boards_with_command.last().unwrap();
-    let last_winning_board = game.boards.get(last_index).unwrap();
-    let score = last_winning_board.get_score();
+        (0..self.boards.len())
+            .map(|board_index| BoardOdds {
+                board_index,
+                win_probability: wins[board_index] as f64 / rounds as f64,
+                expected_call_index: (wins[board_index] > 0)
+                    .then(|| call_index_sums[board_index] as f64 / wins[board_index] as f64),
+                last_probability: last[board_index] as f64 / rounds as f64,
+            })
+            .collect()
+    }
+}
 
-    score * command
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Win {
+    pub board_index: usize,
+    pub winning_call: u32,
+    pub score: u32,
+    pub call_index: usize,
 }
 
-#[derive(Debug, Clone)]
-pub struct BingoGame {
-    pub commands: Vec<u32>,
-    pub boards: Vec<BingoBoard>,
+/// Outcome of [`BingoGame::analyse`] for a single board. The expected call
+/// index only counts the rounds the board actually won.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardOdds {
+    pub board_index: usize,
+    pub win_probability: f64,
+    pub expected_call_index: Option<f64>,
+    pub last_probability: f64,
 }
 
 #[derive(Debug, Clone)]
-pub struct BingoBoard(DMatrix<BoardField>);
+pub struct BingoBoard {
+    pub fields: DMatrix<BoardField>,
+    cells: HashMap<u32, Vec<(usize, usize)>>,
+    // Cell sets that win when fully marked, how many of their cells are
+    // marked, and which sets every cell belongs to
+    lines: Vec<Vec<(usize, usize)>>,
+    line_marks: Vec<usize>,
+    cell_lines: DMatrix<Vec<usize>>,
+    won: bool,
+    unmarked_sum: u32,
+}
 
 impl BingoBoard {
-    pub fn check_value(&mut self, value: u32) {
-        let matrix = &mut self.0;
+    pub fn new(fields: DMatrix<BoardField>) -> Self {
+        // Rows and columns fit boards of any shape
+        Self::with_patterns(fields, &[WinPattern::Rows, WinPattern::Columns]).unwrap()
+    }
 
-        matrix.iter_mut().for_each(|e| {
-            if e.value == value {
-                e.checked = true;
+    pub fn with_patterns(
+        fields: DMatrix<BoardField>,
+        patterns: &[WinPattern],
+    ) -> Result<Self, PatternError> {
+        let (rows, columns) = fields.shape();
+
+        let mut cells: HashMap<u32, Vec<(usize, usize)>> = HashMap::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let field = &fields[(row, column)];
+                cells.entry(field.value).or_default().push((row, column));
             }
-        });
-    }
+        }
 
-    pub fn has_won(&self) -> bool {
-        let matrix = &self.0;
+        let lines = patterns
+            .iter()
+            .map(|p| p.lines(rows, columns))
+            .collect::<Result<Vec<_>, PatternError>>()?
+            .into_iter()
+            .flatten()
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<Vec<(usize, usize)>>>();
+        let mut cell_lines = DMatrix::from_element(rows, columns, vec![]);
+        for (i, line) in lines.iter().enumerate() {
+            for &cell in line {
+                cell_lines[cell].push(i);
+            }
+        }
 
-        for column in matrix.column_iter() {
-            let won = column.iter().all(|e| e.checked);
-            if won {
-                return true;
+        let mut board = Self {
+            line_marks: vec![0; lines.len()],
+            unmarked_sum: fields.iter().map(|f| f.value).sum(),
+            won: false,
+            cells,
+            lines,
+            cell_lines,
+            fields,
+        };
+
+        // Keep fields that were already checked when parsed consistent
+        for row in 0..rows {
+            for column in 0..columns {
+                if board.fields[(row, column)].checked {
+                    board.fields[(row, column)].checked = false;
+                    board.mark(row, column);
+                }
             }
         }
 
-        for row in matrix.row_iter() {
-            let won = row.iter().all(|e| e.checked);
-            if won {
-                return true;
+        Ok(board)
+    }
+
+    /// Marks every field holding `value`, returns whether any field changed.
+    pub fn check_value(&mut self, value: u32) -> bool {
+        let cells = match self.cells.get(&value) {
+            Some(cells) => cells.clone(),
+            None => return false,
+        };
+
+        cells.into_iter().fold(false, |changed, (row, column)| {
+            self.mark(row, column) || changed
+        })
+    }
+
+    fn mark(&mut self, row: usize, column: usize) -> bool {
+        let field = &mut self.fields[(row, column)];
+        if field.checked {
+            return false;
+        }
+
+        field.checked = true;
+        self.unmarked_sum -= field.value;
+        for &line in &self.cell_lines[(row, column)] {
+            self.line_marks[line] += 1;
+            if self.line_marks[line] == self.lines[line].len() {
+                self.won = true;
             }
         }
 
-        false
+        true
+    }
+
+    pub fn has_won(&self) -> bool {
+        self.won
     }
 
     pub fn get_score(&self) -> u32 {
-        let matrix = &self.0;
+        self.unmarked_sum
+    }
+}
+
+/// Sets of cells that win a board once all of them are marked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WinPattern {
+    Rows,
+    Columns,
+    /// Both diagonals starting in the top corners, as long as the shorter side
+    Diagonals,
+    FourCorners,
+    Blackout,
+    /// Marked cells of the mask, which has to match the board's shape
+    Mask(DMatrix<bool>),
+}
+
+impl WinPattern {
+    /// Fails for masks whose shape differs from the board's.
+    pub fn lines(
+        &self,
+        rows: usize,
+        columns: usize,
+    ) -> Result<Vec<Vec<(usize, usize)>>, PatternError> {
+        if let WinPattern::Mask(mask) = self {
+            if mask.shape() != (rows, columns) {
+                return Err(PatternError::MaskShape {
+                    mask: mask.shape(),
+                    board: (rows, columns),
+                });
+            }
+        }
+        if rows == 0 || columns == 0 {
+            return Ok(vec![]);
+        }
 
-        matrix.iter().filter(|f| !f.checked).map(|f| f.value).sum()
+        Ok(match self {
+            WinPattern::Rows => (0..rows)
+                .map(|r| (0..columns).map(|c| (r, c)).collect())
+                .collect(),
+            WinPattern::Columns => (0..columns)
+                .map(|c| (0..rows).map(|r| (r, c)).collect())
+                .collect(),
+            WinPattern::Diagonals => {
+                let len = rows.min(columns);
+                vec![
+                    (0..len).map(|i| (i, i)).collect(),
+                    (0..len).map(|i| (i, columns - 1 - i)).collect(),
+                ]
+            }
+            WinPattern::FourCorners => {
+                let mut corners = vec![
+                    (0, 0),
+                    (0, columns - 1),
+                    (rows - 1, 0),
+                    (rows - 1, columns - 1),
+                ];
+                corners.sort_unstable();
+                corners.dedup();
+                vec![corners]
+            }
+            WinPattern::Blackout => vec![(0..rows)
+                .flat_map(|r| (0..columns).map(move |c| (r, c)))
+                .collect()],
+            WinPattern::Mask(mask) => vec![(0..rows)
+                .flat_map(|r| (0..columns).map(move |c| (r, c)))
+                .filter(|&cell| mask[cell])
+                .collect()],
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PatternError {
+    /// Shapes as `(rows, columns)`
+    MaskShape {
+        mask: (usize, usize),
+        board: (usize, usize),
+    },
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::MaskShape { mask, board } => write!(
+                f,
+                "mask of {}x{} doesn't match board of {}x{}",
+                mask.0, mask.1, board.0, board.1
+            ),
+        }
     }
 }
 
+impl std::error::Error for PatternError {}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct BoardField {
     pub value: u32,
@@ -153,28 +445,13 @@ pub struct BoardField {
 }
 
 #[cfg(test)]
-mod test_day03 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
-
-22 13 17 11  0
- 8  2 23  4 24
-21  9 14 16  7
- 6 10  3 18  5
- 1 12 20 15 19
-
- 3 15  0  2 22
- 9 18 13 17  5
-19  8  7 25 23
-20 11 10 24  4
-14 21 16 12  6
-
-14 21 17 24  4
-10 16 15  9 19
-18  8 23 26 20
-22 11 13  6  5
- 2  0 12  3  7";
+mod test_day04 {
+    use super::{
+        input_generator, parse_board, solve_part1, solve_part2, BingoBoard, PatternError, Win,
+        WinPattern, EXAMPLE as INPUT,
+    };
+    use nalgebra::DMatrix;
+    use std::num::NonZeroUsize;
 
     #[test]
     fn part1() {
@@ -187,4 +464,94 @@ mod test_day03 {
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 1924);
     }
+
+    #[test]
+    fn timeline() {
+        let input = input_generator(INPUT);
+        let timeline = input.play();
+
+        assert_eq!(
+            timeline.iter().map(|w| w.board_index).collect::<Vec<_>>(),
+            vec![2, 0, 1]
+        );
+        assert_eq!(
+            timeline[0],
+            Win {
+                board_index: 2,
+                winning_call: 24,
+                score: 4512,
+                call_index: 11,
+            }
+        );
+        assert_eq!(timeline[2].winning_call, 13);
+    }
+
+    #[test]
+    fn patterns() {
+        let input = input_generator(INPUT);
+
+        // 4, 9, 23, 11 and 2 complete the third board's anti-diagonal
+        let game = input.with_patterns(&[WinPattern::Diagonals]).unwrap();
+        let first = game.play()[0];
+        assert_eq!((first.board_index, first.call_index), (2, 7));
+
+        // Every number gets called eventually, leaving nothing unmarked
+        let game = input.with_patterns(&[WinPattern::Blackout]).unwrap();
+        let timeline = game.play();
+        assert_eq!(timeline.len(), 3);
+        assert!(timeline.iter().all(|w| w.score == 0));
+
+        let mut mask = DMatrix::from_element(5, 5, false);
+        mask[(2, 2)] = true;
+        let game = input.with_patterns(&[WinPattern::Mask(mask)]).unwrap();
+        assert_eq!(game.play()[0].winning_call, 7);
+
+        let mask = DMatrix::from_element(4, 5, true);
+        assert_eq!(
+            input.with_patterns(&[WinPattern::Mask(mask)]).unwrap_err(),
+            PatternError::MaskShape {
+                mask: (4, 5),
+                board: (5, 5)
+            }
+        );
+    }
+
+    #[test]
+    fn non_square_board() {
+        let mut board = parse_board(
+            "1 2 3
+4 5 6",
+        );
+        assert_eq!(board.fields.shape(), (2, 3));
+
+        board.check_value(2);
+        board.check_value(5);
+        assert!(board.has_won());
+
+        let mut board =
+            BingoBoard::with_patterns(board.fields, &[WinPattern::FourCorners]).unwrap();
+        for value in [1, 3, 4] {
+            board.check_value(value);
+        }
+        assert!(!board.has_won());
+        board.check_value(6);
+        assert!(board.has_won());
+    }
+
+    #[test]
+    fn analyse() {
+        let input = input_generator(INPUT);
+        let rounds = NonZeroUsize::new(500).unwrap();
+        let odds = input.analyse(rounds, 2021);
+
+        assert_eq!(odds, input.analyse(rounds, 2021));
+        assert_eq!(odds.len(), 3);
+        for board in &odds {
+            // Every number gets called, so each board wins eventually
+            assert_eq!(board.win_probability, 1.0);
+            let expected = board.expected_call_index.unwrap();
+            assert!((4.0..27.0).contains(&expected));
+        }
+        assert!(odds.iter().map(|b| b.last_probability).sum::<f64>() >= 1.0);
+    }
 }
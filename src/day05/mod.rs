@@ -1,11 +1,20 @@
+pub mod naive;
+
+use crate::input::normalize;
+use crate::report::{self, Diagnostics};
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::Rng;
+use serde_json::json;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 type InputType = LineSegment;
 
 #[aoc_generator(day05)]
 pub fn input_generator(input: &str) -> Vec<InputType> {
+    let input = normalize(input);
     input
         .lines()
         .map(|l| l.parse())
@@ -15,117 +24,240 @@ pub fn input_generator(input: &str) -> Vec<InputType> {
 
 #[aoc(day05, part1)]
 pub fn solve_part1(input: &[InputType]) -> usize {
-    let mut map: HashMap<(i32, i32), i32> = HashMap::new();
+    count_overlaps(input, &[Orientation::Horizontal, Orientation::Vertical]).unwrap()
+}
 
-    let segments = input
-        .iter()
-        .filter(|ls| ls.x1 == ls.x2 || ls.y1 == ls.y2)
-        .cloned()
-        .collect::<Vec<InputType>>();
+#[aoc(day05, part2)]
+pub fn solve_part2(input: &[InputType]) -> usize {
+    count_overlaps(
+        input,
+        &[
+            Orientation::Horizontal,
+            Orientation::Vertical,
+            Orientation::Diagonal,
+        ],
+    )
+    .unwrap()
+}
 
-    // Draw lines
-    for segment in segments {
-        // X
-        if segment.x1 != segment.x2 {
-            let mut x_coords = vec![segment.x1, segment.x2];
-            x_coords.sort_unstable();
-            for x in x_coords[0]..x_coords[1] + 1 {
-                increase_map_value(x, segment.y1, &mut map);
-            }
-        }
+pub const EXAMPLE: &str = "0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2";
 
-        // Y
-        if segment.y1 != segment.y2 {
-            let mut y_coords = vec![segment.y1, segment.y2];
-            y_coords.sort_unstable();
-            for y in y_coords[0]..y_coords[1] + 1 {
-                increase_map_value(segment.x1, y, &mut map);
-            }
-        }
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u32 = 5;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Vec<InputType>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics(input: &Self::Input) -> Diagnostics {
+        let count = |orientation| {
+            input
+                .iter()
+                .filter(|s| s.orientation() == Ok(orientation))
+                .count()
+        };
+
+        report::diagnostics(json!({
+            "segments": input.len(),
+            "horizontal": count(Orientation::Horizontal),
+            "vertical": count(Orientation::Vertical),
+            "diagonal": count(Orientation::Diagonal),
+            "intersecting_pairs": intersections(input).len(),
+        }))
     }
 
-    map.iter().filter(|&(_, v)| v > &1).count()
+    /// Horizontal, vertical and diagonal segments of up to 100 points on a
+    /// 1000x1000 board.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let room = |from: i32, step: i32| match step {
+            1 => 999 - from,
+            -1 => from,
+            _ => i32::MAX,
+        };
+
+        let segments = (0..scale.max(1)).map(|_| {
+            let (x1, y1) = (rng.gen_range(0..1000), rng.gen_range(0..1000));
+            let (dx, dy) = loop {
+                let (dx, dy) = (rng.gen_range(-1..=1), rng.gen_range(-1..=1));
+                if (dx, dy) != (0, 0) {
+                    break (dx, dy);
+                }
+            };
+            let length = rng.gen_range(0..100).min(room(x1, dx)).min(room(y1, dy));
+
+            format!("{},{} -> {},{}", x1, y1, x1 + dx * length, y1 + dy * length)
+        });
+        Some(itertools::join(segments, "\n"))
+    }
 }
 
-#[aoc(day05, part2)]
-pub fn solve_part2(input: &[InputType]) -> usize {
-    let mut map: HashMap<(i32, i32), i32> = HashMap::new();
+/// Counts the points covered by at least two of the segments whose
+/// orientation is listed in `orientations`. Segments that are neither axis
+/// aligned nor at 45° are skipped, unless diagonals are counted.
+///
+/// Sweeps over the x axis. Columns in which only horizontal segments are
+/// active look the same, so those runs are counted once and multiplied by
+/// their width, which keeps long axis-aligned segments cheap regardless of
+/// the coordinate range.
+pub fn count_overlaps(
+    segments: &[LineSegment],
+    orientations: &[Orientation],
+) -> Result<usize, SegmentError> {
+    let mut filtered = vec![];
+    for segment in segments {
+        let orientation = match segment.orientation() {
+            Ok(orientation) => orientation,
+            // Other angles are only an error once diagonals are counted
+            Err(error) if orientations.contains(&Orientation::Diagonal) => return Err(error),
+            Err(_) => continue,
+        };
+        if orientations.contains(&orientation) {
+            filtered.push((segment.normalized(), orientation));
+        }
+    }
+    let mut segments = filtered;
+    segments.sort_unstable_by_key(|(s, _)| s.x1);
 
-    // Only horizontal and vertical
-    let segments = input
+    let mut columns = segments
         .iter()
-        .filter(|ls| ls.x1 == ls.x2 || ls.y1 == ls.y2)
-        .cloned()
-        .collect::<Vec<InputType>>();
-    // Draw lines
-    for segment in segments {
-        // X
-        if segment.x1 != segment.x2 {
-            let mut x_coords = vec![segment.x1, segment.x2];
-            x_coords.sort_unstable();
-            for x in x_coords[0]..x_coords[1] + 1 {
-                increase_map_value(x, segment.y1, &mut map);
-            }
+        .flat_map(|(s, _)| [s.x1 as i64, s.x2 as i64 + 1])
+        .collect::<Vec<i64>>();
+    columns.sort_unstable();
+    columns.dedup();
+
+    let mut overlaps = 0;
+    let mut next = 0;
+    let mut active: Vec<&(LineSegment, Orientation)> = vec![];
+
+    for (&start, &end) in columns.iter().zip(columns.iter().skip(1)) {
+        active.retain(|(s, _)| s.x2 as i64 >= start);
+        while next < segments.len() && segments[next].0.x1 as i64 == start {
+            active.push(&segments[next]);
+            next += 1;
         }
 
-        // Y
-        if segment.y1 != segment.y2 {
-            let mut y_coords = vec![segment.y1, segment.y2];
-            y_coords.sort_unstable();
-            for y in y_coords[0]..y_coords[1] + 1 {
-                increase_map_value(segment.x1, y, &mut map);
+        let has_diagonal = active.iter().any(|(_, o)| *o == Orientation::Diagonal);
+        match has_diagonal {
+            true => {
+                for x in start..end {
+                    overlaps += column_overlaps(&active, x);
+                }
             }
+            false => overlaps += column_overlaps(&active, start) * (end - start) as usize,
         }
     }
 
-    let segments = input
+    Ok(overlaps)
+}
+
+fn column_overlaps(active: &[&(LineSegment, Orientation)], x: i64) -> usize {
+    let mut events = active
         .iter()
-        .filter(|ls| ls.x1 != ls.x2 && ls.y1 != ls.y2)
-        .cloned()
-        .collect::<Vec<InputType>>();
-
-    for s in segments {
-        let x_adder = match s.x1.cmp(&s.x2) {
-            Ordering::Less => 1,
-            Ordering::Greater => -1,
-            Ordering::Equal => {
-                unreachable!();
-            }
-        };
+        .flat_map(|(s, o)| {
+            let (lo, hi) = match o {
+                Orientation::Horizontal => (s.y1 as i64, s.y1 as i64),
+                Orientation::Vertical => (s.y1.min(s.y2) as i64, s.y1.max(s.y2) as i64),
+                Orientation::Diagonal => {
+                    let y = s.y1 as i64 + (x - s.x1 as i64) * (s.y2 - s.y1).signum() as i64;
+                    (y, y)
+                }
+            };
+            [(lo, 1), (hi + 1, -1)]
+        })
+        .collect::<Vec<(i64, i32)>>();
+    events.sort_unstable();
 
-        let y_adder = match s.y1.cmp(&s.y2) {
-            Ordering::Less => 1,
-            Ordering::Greater => -1,
-            Ordering::Equal => {
-                unreachable!();
-            }
-        };
+    let mut count = 0;
+    let mut depth = 0;
+    let mut covered_since = 0;
+
+    for (y, change) in events {
+        if depth < 2 && depth + change >= 2 {
+            covered_since = y;
+        } else if depth >= 2 && depth + change < 2 {
+            count += (y - covered_since) as usize;
+        }
+        depth += change;
+    }
+
+    count
+}
+
+/// All pairs of segments sharing at least one lattice point, as
+/// `(index, index, intersection)` with the lower index first.
+///
+/// Segments are sorted by their left end and swept along the x axis; only
+/// pairs whose x ranges are active at the same time and whose y ranges
+/// overlap get the exact intersection test.
+pub fn intersections(segments: &[LineSegment]) -> Vec<(usize, usize, Intersection)> {
+    let mut order = (0..segments.len()).collect::<Vec<usize>>();
+    order.sort_unstable_by_key(|&i| segments[i].x1.min(segments[i].x2));
+
+    let y_range = |s: &LineSegment| (s.y1.min(s.y2), s.y1.max(s.y2));
+    let mut result = vec![];
+    let mut active: Vec<usize> = vec![];
+
+    for i in order {
+        let segment = &segments[i];
+        let left = segment.x1.min(segment.x2);
+        active.retain(|&j| segments[j].x1.max(segments[j].x2) >= left);
 
-        let mut x = s.x1;
-        let mut y = s.y1;
+        let (lo, hi) = y_range(segment);
+        for &j in &active {
+            let (other_lo, other_hi) = y_range(&segments[j]);
+            if other_hi < lo || other_lo > hi {
+                continue;
+            }
 
-        while x != s.x2 && y != s.y2 {
-            increase_map_value(x, y, &mut map);
-            x += x_adder;
-            y += y_adder;
+            if let Some(intersection) = segment.intersection(&segments[j]) {
+                result.push((i.min(j), i.max(j), intersection));
+            }
         }
 
-        increase_map_value(s.x2, s.y2, &mut map);
+        active.push(i);
     }
 
-    map.iter().filter(|&(_, v)| v > &1).count()
+    result.sort_unstable_by_key(|&(i, j, _)| (i, j));
+    result
 }
 
-fn increase_map_value(x: i32, y: i32, map: &mut HashMap<(i32, i32), i32>) {
-    match map.get_mut(&(x, y)) {
-        None => {
-            map.insert((x, y), 1);
-        }
-        Some(v) => *v += 1,
-    };
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
 }
 
-#[derive(Debug, Clone)]
+fn gcd(a: i128, b: i128) -> i128 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct LineSegment {
     pub x1: i32,
     pub y1: i32,
@@ -133,14 +265,150 @@ pub struct LineSegment {
     pub y2: i32,
 }
 
+impl LineSegment {
+    pub fn orientation(&self) -> Result<Orientation, SegmentError> {
+        let dx = (self.x2 - self.x1).abs();
+        let dy = (self.y2 - self.y1).abs();
+
+        if dy == 0 {
+            Ok(Orientation::Horizontal)
+        } else if dx == 0 {
+            Ok(Orientation::Vertical)
+        } else if dx == dy {
+            Ok(Orientation::Diagonal)
+        } else {
+            Err(SegmentError::UnsupportedAngle(*self))
+        }
+    }
+
+    pub fn points(&self) -> Result<Points, SegmentError> {
+        self.orientation()?;
+
+        Ok(Points {
+            x: self.x1,
+            y: self.y1,
+            dx: (self.x2 - self.x1).signum(),
+            dy: (self.y2 - self.y1).signum(),
+            remaining: (self.x2 - self.x1).abs().max((self.y2 - self.y1).abs()) as usize + 1,
+        })
+    }
+
+    /// Same segment, with the endpoints swapped if needed so that `x1 <= x2`.
+    pub fn normalized(&self) -> Self {
+        match self.x1.cmp(&self.x2) {
+            Ordering::Greater => Self {
+                x1: self.x2,
+                y1: self.y2,
+                x2: self.x1,
+                y2: self.y1,
+            },
+            _ => *self,
+        }
+    }
+
+    /// Pixels approximating the segment at any slope, using Bresenham's
+    /// algorithm. Matches `points()` for axis-aligned and diagonal segments.
+    pub fn raster(&self) -> Raster {
+        let dx = (self.x2 - self.x1).abs();
+        let dy = -(self.y2 - self.y1).abs();
+
+        Raster {
+            x: self.x1,
+            y: self.y1,
+            x2: self.x2,
+            y2: self.y2,
+            sx: (self.x2 - self.x1).signum(),
+            sy: (self.y2 - self.y1).signum(),
+            dx,
+            dy,
+            err: dx + dy,
+            done: false,
+        }
+    }
+
+    /// Lattice points shared by both segments, computed exactly in integer
+    /// arithmetic. Collinear segments may share a whole run of points, which
+    /// is returned as the overlapping sub-segment.
+    pub fn intersection(&self, other: &LineSegment) -> Option<Intersection> {
+        let p = (self.x1 as i128, self.y1 as i128);
+        let r = ((self.x2 - self.x1) as i128, (self.y2 - self.y1) as i128);
+        let q = (other.x1 as i128, other.y1 as i128);
+        let s = ((other.x2 - other.x1) as i128, (other.y2 - other.y1) as i128);
+        let qp = (q.0 - p.0, q.1 - p.1);
+
+        if r == (0, 0) {
+            return other
+                .contains(self.x1, self.y1)
+                .then_some(Intersection::Point((self.x1, self.y1)));
+        }
+        if s == (0, 0) {
+            return self
+                .contains(other.x1, other.y1)
+                .then_some(Intersection::Point((other.x1, other.y1)));
+        }
+
+        let denom = cross(r, s);
+        if denom != 0 {
+            // p + t·r = q + u·s with t = t_num / denom and u = u_num / denom
+            let (t_num, u_num, denom) = match denom < 0 {
+                true => (-cross(qp, s), -cross(qp, r), -denom),
+                false => (cross(qp, s), cross(qp, r), denom),
+            };
+            if !(0..=denom).contains(&t_num) || !(0..=denom).contains(&u_num) {
+                return None;
+            }
+            if (r.0 * t_num) % denom != 0 || (r.1 * t_num) % denom != 0 {
+                return None;
+            }
+
+            let x = p.0 + r.0 * t_num / denom;
+            let y = p.1 + r.1 * t_num / denom;
+            return Some(Intersection::Point((x as i32, y as i32)));
+        }
+
+        if cross(qp, r) != 0 {
+            return None;
+        }
+
+        // Collinear: every lattice point on the line is p + k·step
+        let g = gcd(r.0.abs(), r.1.abs());
+        let step = (r.0 / g, r.1 / g);
+        let index = |x: i128, y: i128| match step.0 {
+            0 => (y - p.1) / step.1,
+            _ => (x - p.0) / step.0,
+        };
+        let (a, b) = (index(q.0, q.1), index(q.0 + s.0, q.1 + s.1));
+        let lo = a.min(b).max(0);
+        let hi = a.max(b).min(g);
+
+        let point = |k: i128| ((p.0 + k * step.0) as i32, (p.1 + k * step.1) as i32);
+        match lo.cmp(&hi) {
+            Ordering::Greater => None,
+            Ordering::Equal => Some(Intersection::Point(point(lo))),
+            Ordering::Less => {
+                let ((x1, y1), (x2, y2)) = (point(lo), point(hi));
+                Some(Intersection::Overlap(LineSegment { x1, y1, x2, y2 }))
+            }
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let r = ((self.x2 - self.x1) as i128, (self.y2 - self.y1) as i128);
+        let d = ((x - self.x1) as i128, (y - self.y1) as i128);
+
+        cross(r, d) == 0
+            && (self.x1.min(self.x2)..=self.x1.max(self.x2)).contains(&x)
+            && (self.y1.min(self.y2)..=self.y1.max(self.y2)).contains(&y)
+    }
+}
+
 impl FromStr for LineSegment {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let line = s
             .split(" -> ")
-            .map(|x| x.split(',').map(|y| y.to_string()).collect::<Vec<String>>())
-            .flatten()
+            .flat_map(|x| x.split(',').map(|y| y.to_string()).collect::<Vec<String>>())
             .map(|x| x.parse().unwrap())
             .collect::<Vec<i32>>();
 
@@ -153,20 +421,120 @@ impl FromStr for LineSegment {
     }
 }
 
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+    Diagonal,
+}
+
+#[derive(Debug, Clone)]
+pub struct Points {
+    x: i32,
+    y: i32,
+    dx: i32,
+    dy: i32,
+    remaining: usize,
+}
+
+impl Iterator for Points {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let point = (self.x, self.y);
+        self.x += self.dx;
+        self.y += self.dy;
+        self.remaining -= 1;
+
+        Some(point)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Points {}
+
+#[derive(Debug, Clone)]
+pub struct Raster {
+    x: i32,
+    y: i32,
+    x2: i32,
+    y2: i32,
+    sx: i32,
+    sy: i32,
+    dx: i32,
+    dy: i32,
+    err: i32,
+    done: bool,
+}
+
+impl Iterator for Raster {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let point = (self.x, self.y);
+        if point == (self.x2, self.y2) {
+            self.done = true;
+            return Some(point);
+        }
+
+        let e2 = 2 * self.err;
+        if e2 >= self.dy {
+            self.err += self.dy;
+            self.x += self.sx;
+        }
+        if e2 <= self.dx {
+            self.err += self.dx;
+            self.y += self.sy;
+        }
+
+        Some(point)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Intersection {
+    Point((i32, i32)),
+    Overlap(LineSegment),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SegmentError {
+    UnsupportedAngle(LineSegment),
+}
+
+impl Display for SegmentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentError::UnsupportedAngle(s) => write!(
+                f,
+                "segment {},{} -> {},{} is neither axis-aligned nor diagonal",
+                s.x1, s.y1, s.x2, s.y2
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SegmentError {}
+
 #[cfg(test)]
 mod test_day05 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "0,9 -> 5,9
-8,0 -> 0,8
-9,4 -> 3,4
-2,2 -> 2,1
-7,0 -> 7,4
-6,4 -> 2,0
-0,9 -> 2,9
-3,4 -> 1,4
-0,0 -> 8,8
-5,5 -> 8,2";
+    use super::{
+        count_overlaps, input_generator, intersections, naive, solve_part1, solve_part2,
+        Intersection, LineSegment, Orientation, SegmentError, EXAMPLE as INPUT,
+    };
+    use proptest::prelude::*;
+    use std::collections::HashMap;
 
     #[test]
     fn part1() {
@@ -179,4 +547,175 @@ mod test_day05 {
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 12);
     }
+
+    #[test]
+    fn other_angles() {
+        let input = input_generator(&format!("{}\n0,0 -> 2,1", INPUT));
+        assert_eq!(solve_part1(&input), 5);
+
+        let all = [
+            Orientation::Horizontal,
+            Orientation::Vertical,
+            Orientation::Diagonal,
+        ];
+        assert_eq!(
+            count_overlaps(&input, &all).err(),
+            Some(SegmentError::UnsupportedAngle(input[10]))
+        );
+    }
+
+    #[test]
+    fn points() {
+        let segment: LineSegment = "8,0 -> 5,3".parse().unwrap();
+        assert_eq!(
+            segment.points().unwrap().collect::<Vec<_>>(),
+            vec![(8, 0), (7, 1), (6, 2), (5, 3)]
+        );
+
+        let segment: LineSegment = "0,0 -> 2,1".parse().unwrap();
+        assert_eq!(
+            segment.points().err(),
+            Some(SegmentError::UnsupportedAngle(segment))
+        );
+    }
+
+    #[test]
+    fn large_coordinates() {
+        let input = input_generator(
+            "0,5 -> 4000000,5
+1000000,0 -> 1000000,9
+3000000,5 -> 2000000,5
+999998,3 -> 1000003,8
+1000004,3 -> 999999,8",
+        );
+
+        assert_eq!(
+            count_overlaps(&input, &[Orientation::Horizontal, Orientation::Vertical]).unwrap(),
+            1000002
+        );
+        assert_eq!(
+            count_overlaps(
+                &input,
+                &[
+                    Orientation::Horizontal,
+                    Orientation::Vertical,
+                    Orientation::Diagonal
+                ]
+            )
+            .unwrap(),
+            1000005
+        );
+    }
+
+    #[test]
+    fn matches_point_map() {
+        let input = input_generator(INPUT);
+        let mut map: HashMap<(i32, i32), usize> = HashMap::new();
+        input
+            .iter()
+            .flat_map(|s| s.points().unwrap())
+            .for_each(|p| *map.entry(p).or_insert(0) += 1);
+
+        assert_eq!(
+            solve_part2(&input),
+            map.values().filter(|&&v| v > 1).count()
+        );
+    }
+
+    #[test]
+    fn raster() {
+        let segment: LineSegment = "0,0 -> 5,2".parse().unwrap();
+        assert_eq!(
+            segment.raster().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]
+        );
+
+        for segment in input_generator(INPUT) {
+            assert!(segment.raster().eq(segment.points().unwrap()));
+        }
+    }
+
+    #[test]
+    fn intersection() {
+        let parse = |s: &str| s.parse::<LineSegment>().unwrap();
+
+        // Crossing on a lattice point, and crossing between lattice points
+        assert_eq!(
+            parse("0,0 -> 4,2").intersection(&parse("0,2 -> 4,0")),
+            Some(Intersection::Point((2, 1)))
+        );
+        assert_eq!(parse("0,0 -> 1,1").intersection(&parse("0,1 -> 1,0")), None);
+
+        // Collinear segments overlapping in a run of points, and touching at an end
+        assert_eq!(
+            parse("0,0 -> 6,3").intersection(&parse("10,5 -> 4,2")),
+            Some(Intersection::Overlap(parse("4,2 -> 6,3")))
+        );
+        assert_eq!(
+            parse("0,0 -> 6,3").intersection(&parse("6,3 -> 10,5")),
+            Some(Intersection::Point((6, 3)))
+        );
+
+        let segments = input_generator(INPUT);
+        let pairs = intersections(&segments);
+        let brute_force = (0..segments.len())
+            .flat_map(|i| (i + 1..segments.len()).map(move |j| (i, j)))
+            .filter_map(|(i, j)| Some((i, j, segments[i].intersection(&segments[j])?)))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, brute_force);
+    }
+
+    fn segment() -> impl Strategy<Value = LineSegment> {
+        // The last two are neither axis aligned nor diagonal
+        const DIRECTIONS: [(i32, i32); 10] = [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+            (2, 1),
+            (-1, 3),
+        ];
+
+        (-20..50, -20..50, 0..DIRECTIONS.len(), 0..30).prop_map(|(x1, y1, direction, length)| {
+            let (dx, dy) = DIRECTIONS[direction];
+            LineSegment {
+                x1,
+                y1,
+                x2: x1 + dx * length,
+                y2: y1 + dy * length,
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(segments in prop::collection::vec(segment(), 0..40)) {
+            let axis = [Orientation::Horizontal, Orientation::Vertical];
+            let all = [Orientation::Horizontal, Orientation::Vertical, Orientation::Diagonal];
+
+            prop_assert_eq!(
+                count_overlaps(&segments, &axis),
+                naive::count_overlaps(&segments, &axis)
+            );
+            prop_assert!(count_overlaps(&segments, &axis).is_ok());
+            prop_assert_eq!(
+                count_overlaps(&segments, &all),
+                naive::count_overlaps(&segments, &all)
+            );
+
+            let supported = segments
+                .iter()
+                .filter(|s| s.orientation().is_ok())
+                .copied()
+                .collect::<Vec<LineSegment>>();
+            prop_assert_eq!(
+                count_overlaps(&supported, &all).unwrap(),
+                naive::count_overlaps(&supported, &all).unwrap()
+            );
+        }
+    }
 }
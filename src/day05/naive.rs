@@ -0,0 +1,26 @@
+//! Straightforward solution, counting every point of every segment.
+
+use super::{LineSegment, Orientation, SegmentError};
+use std::collections::HashMap;
+
+pub fn count_overlaps(
+    segments: &[LineSegment],
+    orientations: &[Orientation],
+) -> Result<usize, SegmentError> {
+    let mut counts = HashMap::new();
+
+    for segment in segments {
+        let orientation = match segment.orientation() {
+            Ok(orientation) => orientation,
+            Err(error) if orientations.contains(&Orientation::Diagonal) => return Err(error),
+            Err(_) => continue,
+        };
+        if orientations.contains(&orientation) {
+            for point in segment.points()? {
+                *counts.entry(point).or_insert(0) += 1;
+            }
+        }
+    }
+
+    Ok(counts.values().filter(|&&count| count > 1).count())
+}
@@ -1,7 +1,17 @@
-use std::collections::HashMap;
+pub mod naive;
+
+use crate::animation::Recorder;
+use crate::export::Grid;
+use crate::input::normalize;
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::Rng;
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroU64;
 
 #[aoc_generator(day06)]
 pub fn input_generator(input: &str) -> Vec<Fish> {
+    let input = normalize(input);
     input
         .split(',')
         .map(|l| {
@@ -22,6 +32,49 @@ pub fn solve_part2(input: &[Fish]) -> u128 {
     Fish::simulate(input, 256)
 }
 
+pub const EXAMPLE: &str = "3,4,3,1,2";
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u32 = 6;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const ANIMATED: bool = true;
+
+    type Input = Vec<Fish>;
+    type Output1 = u128;
+    type Output2 = u128;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn animate(input: &Self::Input, recorder: &mut Recorder) {
+        let mut school = School::new(input, Lifecycle::default()).unwrap();
+        recorder.push(school.to_grid());
+        for _ in 0..80 {
+            school.step().unwrap();
+            recorder.push(school.to_grid());
+        }
+    }
+
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let timers = (0..scale.max(1))
+            .map(|_| rng.gen_range(1..=5).to_string())
+            .collect::<Vec<String>>();
+        Some(timers.join(","))
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub struct Fish {
     pub timer: u32,
@@ -33,35 +86,200 @@ impl Fish {
     }
 
     fn simulate(start_population: &[Fish], days: u32) -> u128 {
-        let mut fish = HashMap::new();
+        let mut school = School::new(start_population, Lifecycle::default()).unwrap();
+        school.advance(days as u64).unwrap();
+        school.total().unwrap()
+    }
+}
 
-        for i in start_population.to_vec() {
-            *fish.entry(i).or_insert(0) += 1;
+/// Timers a fish is set to after spawning (`reset`) and when it is born
+/// (`newborn`).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Lifecycle {
+    pub reset: usize,
+    pub newborn: usize,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            reset: 6,
+            newborn: 8,
         }
+    }
+}
+
+/// Number of fish per timer value. The state never grows past
+/// `newborn + 1` entries, no matter how many days are simulated.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct School {
+    pub lifecycle: Lifecycle,
+    pub counts: Vec<u128>,
+    pub day: u64,
+}
+
+impl School {
+    pub fn new(fish: &[Fish], lifecycle: Lifecycle) -> Result<Self, SimulationError> {
+        if lifecycle.reset > lifecycle.newborn {
+            return Err(SimulationError::InvalidLifecycle(lifecycle));
+        }
+
+        let mut counts = vec![0; lifecycle.newborn + 1];
+        for f in fish {
+            *counts
+                .get_mut(f.timer as usize)
+                .ok_or(SimulationError::InvalidTimer(f.timer))? += 1;
+        }
+
+        Ok(Self {
+            lifecycle,
+            counts,
+            day: 0,
+        })
+    }
+
+    pub fn step(&mut self) -> Result<(), SimulationError> {
+        let Lifecycle { reset, newborn } = self.lifecycle;
+        let spawning = self.counts[0];
 
+        // Rotating moves the spawning fish into the newborn slot, they also
+        // have to be added to whatever ends up in the reset slot
+        let reset_count = match reset < newborn {
+            true => self.counts[reset + 1].checked_add(spawning),
+            false => spawning.checked_mul(2),
+        }
+        .ok_or(SimulationError::Overflow(self.day + 1))?;
+
+        self.counts.rotate_left(1);
+        self.counts[reset] = reset_count;
+        self.day += 1;
+
+        Ok(())
+    }
+
+    pub fn advance(&mut self, days: u64) -> Result<(), SimulationError> {
         for _ in 0..days {
-            let mut new_fish = HashMap::new();
-            for i in &fish {
-                if i.0.timer == 0 {
-                    *new_fish.entry(Fish::new(6)).or_insert(0) += *i.1;
-                    *new_fish.entry(Fish::new(8)).or_insert(0) += *i.1;
-                    continue;
-                }
-
-                *new_fish.entry(Fish::new(i.0.timer - 1)).or_insert(0) += *i.1;
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn total(&self) -> Result<u128, SimulationError> {
+        self.counts
+            .iter()
+            .try_fold(0u128, |acc, c| acc.checked_add(*c))
+            .ok_or(SimulationError::Overflow(self.day))
+    }
+
+    /// Histogram of the timers, scaled to the largest count
+    pub fn to_grid(&self) -> Grid {
+        Grid::bars(&self.counts, 32)
+    }
+
+    /// Number of fish after `days` more days, modulo `modulus`. The transition
+    /// matrix is raised to the power of `days`, so this runs in
+    /// `O(timers³ · log days)` and works for day counts far beyond what
+    /// stepping could reach.
+    pub fn total_after_mod(&self, days: u64, modulus: NonZeroU64) -> u64 {
+        let modulus = modulus.get();
+        let n = self.counts.len();
+        let m = modulus as u128;
+
+        // transition[i][j]: fish with timer i tomorrow per fish with timer j today
+        let mut transition = vec![vec![0; n]; n];
+        for (i, row) in transition.iter_mut().enumerate().take(n - 1) {
+            row[i + 1] = 1;
+        }
+        transition[self.lifecycle.reset][0] += 1;
+        transition[self.lifecycle.newborn][0] += 1;
+        transition
+            .iter_mut()
+            .flatten()
+            .for_each(|v| *v = (*v as u128 % m) as u64);
+
+        let power = mat_pow(transition, days, modulus);
+
+        power
+            .iter()
+            .flat_map(|row| row.iter().zip(&self.counts))
+            .fold(0, |acc, (&factor, &count)| {
+                (acc + factor as u128 * (count % m)) % m
+            }) as u64
+    }
+}
+
+type Matrix = Vec<Vec<u64>>;
+
+fn mat_mul(a: &Matrix, b: &Matrix, modulus: u64) -> Matrix {
+    let n = a.len();
+    let m = modulus as u128;
+    let mut result = vec![vec![0; n]; n];
+
+    for i in 0..n {
+        for k in 0..n {
+            if a[i][k] == 0 {
+                continue;
+            }
+            for j in 0..n {
+                result[i][j] =
+                    ((result[i][j] as u128 + a[i][k] as u128 * b[k][j] as u128) % m) as u64;
             }
-            fish = new_fish;
         }
+    }
 
-        fish.values().sum()
+    result
+}
+
+fn mat_pow(mut base: Matrix, mut exp: u64, modulus: u64) -> Matrix {
+    let n = base.len();
+    let mut result = vec![vec![0; n]; n];
+    for (i, row) in result.iter_mut().enumerate() {
+        row[i] = 1 % modulus;
     }
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mat_mul(&result, &base, modulus);
+        }
+        base = mat_mul(&base, &base, modulus);
+        exp >>= 1;
+    }
+
+    result
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SimulationError {
+    InvalidLifecycle(Lifecycle),
+    InvalidTimer(u32),
+    Overflow(u64),
 }
 
+impl Display for SimulationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimulationError::InvalidLifecycle(l) => write!(
+                f,
+                "reset timer {} is larger than newborn timer {}",
+                l.reset, l.newborn
+            ),
+            SimulationError::InvalidTimer(t) => write!(f, "timer {} is out of range", t),
+            SimulationError::Overflow(day) => write!(f, "fish count overflowed on day {}", day),
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {}
+
 #[cfg(test)]
 mod test_day06 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "3,4,3,1,2";
+    use super::{
+        input_generator, naive, solve_part1, solve_part2, Fish, Lifecycle, School, SimulationError,
+        EXAMPLE as INPUT,
+    };
+    use proptest::prelude::*;
+    use std::num::NonZeroU64;
 
     #[test]
     fn part1() {
@@ -74,4 +292,57 @@ mod test_day06 {
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 26984457539);
     }
+
+    #[test]
+    fn matrix_power() {
+        let input = input_generator(INPUT);
+        let school = School::new(&input, Lifecycle::default()).unwrap();
+
+        assert_eq!(
+            school.total_after_mod(256, NonZeroU64::new(1_000_000_007).unwrap()),
+            26984457539 % 1_000_000_007
+        );
+
+        let lifecycle = Lifecycle {
+            reset: 2,
+            newborn: 4,
+        };
+        let mut stepped = School::new(&input, lifecycle).unwrap();
+        stepped.advance(100).unwrap();
+        let school = School::new(&input, lifecycle).unwrap();
+        assert_eq!(
+            school.total_after_mod(100, NonZeroU64::new(998_244_353).unwrap()) as u128,
+            stepped.total().unwrap() % 998_244_353
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let input = input_generator(INPUT);
+        let mut school = School::new(&input, Lifecycle::default()).unwrap();
+
+        assert!(matches!(
+            school.advance(2000),
+            Err(SimulationError::Overflow(_))
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(
+            timers in prop::collection::vec(0u32..=8, 0..20),
+            (reset, newborn) in (2usize..=8).prop_flat_map(|reset| (Just(reset), reset..=8)),
+            days in 0u64..40,
+        ) {
+            let fish = timers.iter().map(|&t| Fish::new(t.min(newborn as u32))).collect::<Vec<_>>();
+            let lifecycle = Lifecycle { reset, newborn };
+            let expected = naive::simulate(&fish, lifecycle, days);
+
+            let mut school = School::new(&fish, lifecycle).unwrap();
+            let modulus = NonZeroU64::new(1000).unwrap();
+            prop_assert_eq!(school.total_after_mod(days, modulus), expected as u64 % 1000);
+            school.advance(days).unwrap();
+            prop_assert_eq!(school.total().unwrap(), expected as u128);
+        }
+    }
 }
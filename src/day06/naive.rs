@@ -0,0 +1,27 @@
+//! Straightforward solution, simulating every fish on its own. The school
+//! grows exponentially, so this only works for a few dozen days.
+
+use super::{Fish, Lifecycle};
+
+pub fn simulate(fish: &[Fish], lifecycle: Lifecycle, days: u64) -> usize {
+    let mut timers = fish
+        .iter()
+        .map(|f| f.timer as usize)
+        .collect::<Vec<usize>>();
+
+    for _ in 0..days {
+        let mut newborn = 0;
+        for timer in timers.iter_mut() {
+            match *timer {
+                0 => {
+                    *timer = lifecycle.reset;
+                    newborn += 1;
+                }
+                _ => *timer -= 1,
+            }
+        }
+        timers.extend(std::iter::repeat_n(lifecycle.newborn, newborn));
+    }
+
+    timers.len()
+}
@@ -1,5 +1,13 @@
+pub mod naive;
+
+use crate::input::normalize;
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::Rng;
+
 #[aoc_generator(day07)]
 pub fn input_generator(input: &str) -> Vec<Crab> {
+    let input = normalize(input);
     input
         .split(',')
         .map(|l| {
@@ -11,27 +19,108 @@ pub fn input_generator(input: &str) -> Vec<Crab> {
 }
 
 #[aoc(day07, part1)]
-pub fn solve_part1(input: &[Crab]) -> u32 {
-    let min = input.iter().map(|c| c.pos).min().unwrap();
-    let max = input.iter().map(|c| c.pos).max().unwrap();
-
-    (min..=max)
-        .into_iter()
-        .map(|target| Crab::fuel_cost(input, target))
-        .min()
-        .unwrap()
+pub fn solve_part1(input: &[Crab]) -> u64 {
+    Crab::align(input, &Linear).unwrap().fuel
 }
 
 #[aoc(day07, part2)]
-pub fn solve_part2(input: &[Crab]) -> u32 {
-    let min = input.iter().map(|c| c.pos).min().unwrap();
-    let max = input.iter().map(|c| c.pos).max().unwrap();
-
-    (min..=max)
-        .into_iter()
-        .map(|target| Crab::exp_fuel_cost(input, target))
-        .min()
-        .unwrap()
+pub fn solve_part2(input: &[Crab]) -> u64 {
+    Crab::align(input, &Triangular).unwrap().fuel
+}
+
+pub const EXAMPLE: &str = "16,1,2,0,4,2,7,1,2,14";
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u32 = 7;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Vec<Crab>;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let positions = (0..scale.max(1))
+            .map(|_| rng.gen_range(0..2000).to_string())
+            .collect::<Vec<String>>();
+        Some(positions.join(","))
+    }
+}
+
+/// Fuel needed to move a single crab over a distance. Costs are expected to
+/// be convex in the distance, which is what makes the search in
+/// [`Crab::align`] valid.
+pub trait CostModel {
+    fn cost(&self, distance: u64) -> u64;
+
+    /// Range of targets known to contain the optimum, if the model has a
+    /// closed form for it. `positions` is sorted.
+    fn candidates(&self, _positions: &[u32]) -> Option<(u32, u32)> {
+        None
+    }
+}
+
+/// One unit of fuel per step, minimised at the median.
+pub struct Linear;
+
+impl CostModel for Linear {
+    fn cost(&self, distance: u64) -> u64 {
+        distance
+    }
+
+    fn candidates(&self, positions: &[u32]) -> Option<(u32, u32)> {
+        let median = *positions.get(positions.len() / 2)?;
+        Some((median, median))
+    }
+}
+
+/// Each step costs one more than the last, minimised within half a step of
+/// the mean.
+pub struct Triangular;
+
+impl CostModel for Triangular {
+    fn cost(&self, distance: u64) -> u64 {
+        distance * (distance + 1) / 2
+    }
+
+    fn candidates(&self, positions: &[u32]) -> Option<(u32, u32)> {
+        if positions.is_empty() {
+            return None;
+        }
+
+        let sum = positions.iter().map(|&p| p as u64).sum::<u64>();
+        let mean = sum / positions.len() as u64;
+        Some((mean.saturating_sub(1) as u32, mean as u32 + 1))
+    }
+}
+
+impl<F> CostModel for F
+where
+    F: Fn(u64) -> u64,
+{
+    fn cost(&self, distance: u64) -> u64 {
+        self(distance)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Alignment {
+    pub position: u32,
+    pub fuel: u64,
 }
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
@@ -44,29 +133,64 @@ impl Crab {
         Self { pos }
     }
 
-    pub fn fuel_cost(crabs: &[Crab], target: u32) -> u32 {
+    pub fn fuel_cost(crabs: &[Crab], target: u32, model: &impl CostModel) -> u64 {
         crabs
             .iter()
-            .map(|crab| (crab.pos as i32 - target as i32).abs() as u32)
+            .map(|crab| model.cost(crab.pos.abs_diff(target) as u64))
             .sum()
     }
 
-    pub fn exp_fuel_cost(crabs: &[Crab], target: u32) -> u32 {
-        crabs
-            .iter()
-            .map(|crab| {
-                let dist = (crab.pos as i32 - target as i32).abs() as u32;
-                (1..=dist).into_iter().sum::<u32>()
+    pub fn align(crabs: &[Crab], model: &impl CostModel) -> Option<Alignment> {
+        let mut positions = crabs.iter().map(|c| c.pos).collect::<Vec<u32>>();
+        positions.sort_unstable();
+
+        let min = *positions.first()?;
+        let max = *positions.last()?;
+        let fuel = |target| Crab::fuel_cost(crabs, target, model);
+
+        let (lo, hi) = match model.candidates(&positions) {
+            Some((lo, hi)) => (lo.max(min), hi.min(max)),
+            None => {
+                // Total fuel is convex as well, so the optimum is the first
+                // target from which moving right stops paying off
+                let (mut lo, mut hi) = (min, max);
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    if fuel(mid) <= fuel(mid + 1) {
+                        hi = mid;
+                    } else {
+                        lo = mid + 1;
+                    }
+                }
+                (lo, hi)
+            }
+        };
+
+        (lo..=hi)
+            .map(|position| Alignment {
+                position,
+                fuel: fuel(position),
             })
-            .sum()
+            .min_by_key(|a| a.fuel)
     }
 }
 
 #[cfg(test)]
 mod test_day07 {
-    use super::{input_generator, solve_part1, solve_part2};
+    use super::{
+        input_generator, naive, solve_part1, solve_part2, CostModel, Crab, Linear, Triangular,
+        EXAMPLE as INPUT,
+    };
+    use proptest::prelude::*;
 
-    const INPUT: &str = "16,1,2,0,4,2,7,1,2,14";
+    fn assert_matches_naive(crabs: &[Crab], model: &impl CostModel) -> Result<(), TestCaseError> {
+        let fast = Crab::align(crabs, model).unwrap();
+        let expected = naive::align(crabs, model).unwrap();
+
+        prop_assert_eq!(fast.fuel, expected.fuel);
+        prop_assert_eq!(Crab::fuel_cost(crabs, fast.position, model), fast.fuel);
+        Ok(())
+    }
 
     #[test]
     fn part1() {
@@ -79,4 +203,32 @@ mod test_day07 {
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 168);
     }
+
+    #[test]
+    fn custom_cost() {
+        let input = input_generator(INPUT);
+        let models: [&dyn Fn(u64) -> u64; 3] = [&|d| d, &|d| d * (d + 1) / 2, &|d| d * d * d];
+
+        for model in models {
+            let brute_force = (0..=16)
+                .map(|target| Crab::fuel_cost(&input, target, &model))
+                .min()
+                .unwrap();
+            assert_eq!(Crab::align(&input, &model).unwrap().fuel, brute_force);
+        }
+
+        assert_eq!(Crab::align(&input, &Linear).unwrap().position, 2);
+        assert_eq!(Crab::align(&input, &Triangular).unwrap().position, 5);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(positions in prop::collection::vec(0u32..300, 1..40)) {
+            let crabs = positions.into_iter().map(Crab::new).collect::<Vec<_>>();
+
+            assert_matches_naive(&crabs, &Linear)?;
+            assert_matches_naive(&crabs, &Triangular)?;
+            assert_matches_naive(&crabs, &|d: u64| d * d)?;
+        }
+    }
 }
@@ -0,0 +1,16 @@
+//! Straightforward solution, trying every position between the outermost
+//! crabs.
+
+use super::{Alignment, CostModel, Crab};
+
+pub fn align(crabs: &[Crab], model: &impl CostModel) -> Option<Alignment> {
+    let min = crabs.iter().map(|c| c.pos).min()?;
+    let max = crabs.iter().map(|c| c.pos).max()?;
+
+    (min..=max)
+        .map(|position| Alignment {
+            position,
+            fuel: Crab::fuel_cost(crabs, position, model),
+        })
+        .min_by_key(|a| a.fuel)
+}
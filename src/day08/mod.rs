@@ -1,142 +1,306 @@
-use tap::Tap;
+use crate::input::normalize;
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[aoc_generator(day08)]
+pub fn input_generator(input: &str) -> Vec<Entry> {
+    let input = normalize(input);
+    input
+        .lines()
+        .map(|l| l.parse())
+        .collect::<Result<Vec<Entry>, ParseEntryError>>()
+        .unwrap()
+}
 
 #[aoc(day08, part1)]
-pub fn solve_part1(input: &str) -> u32 {
-    let mut total = 0;
-
-    for line in input.lines() {
-        let input_output = line.split(" | ").collect::<Vec<&str>>();
-        if let [_, output] = &input_output[..] {
-            for o in output.split(' ') {
-                total += match o.len() {
-                    2 | 3 | 4 | 7 => 1,
-                    _ => 0,
-                };
-            }
-        }
+pub fn solve_part1(input: &[Entry]) -> u32 {
+    input
+        .iter()
+        .flat_map(|entry| entry.outputs.iter())
+        .filter(|o| matches!(o.len(), 2 | 3 | 4 | 7))
+        .count() as u32
+}
+
+#[aoc(day08, part2)]
+pub fn solve_part2(input: &[Entry]) -> u32 {
+    input.iter().map(|entry| entry.decode().unwrap()).sum()
+}
+
+pub const EXAMPLE: &str =
+    "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u32 = 8;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Vec<Entry>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
     }
 
-    total
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    /// Entries with a random wiring, listing the patterns and their segments
+    /// in random order.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        const DIGITS: [&str; 10] = [
+            "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+        ];
+        let mut rng = synthetic::rng(seed);
+
+        let entries = (0..scale.max(1)).map(|_| {
+            let mut wiring = ('a'..='g').collect::<Vec<char>>();
+            let mut digits = (0..10).collect::<Vec<usize>>();
+            let outputs = (0..4).map(|_| rng.gen_range(0..10)).collect::<Vec<usize>>();
+            wiring.shuffle(&mut rng);
+            digits.shuffle(&mut rng);
+
+            let mut wire = |digit: usize| {
+                let mut pattern = DIGITS[digit]
+                    .bytes()
+                    .map(|s| wiring[(s - b'a') as usize])
+                    .collect::<Vec<char>>();
+                pattern.shuffle(&mut rng);
+                pattern.into_iter().collect::<String>()
+            };
+            let patterns = digits.iter().map(|&d| wire(d)).collect::<Vec<String>>();
+            let outputs = outputs.iter().map(|&d| wire(d)).collect::<Vec<String>>();
+
+            format!("{} | {}", patterns.join(" "), outputs.join(" "))
+        });
+        Some(itertools::join(entries, "\n"))
+    }
 }
 
-#[aoc(day08, part2)]
-pub fn solve_part2(input: &str) -> u32 {
-    let mut result = 0;
-
-    for line in input.lines() {
-        let mut one = None;
-        let mut four = None;
-        let mut seven = None;
-        let mut eight = None;
-
-        let (input, output) = line.split_once(" | ").unwrap();
-        for s in input.split(' ') {
-            match s.len() {
-                2 => one = Some(s),
-                3 => seven = Some(s),
-                4 => four = Some(s),
-                7 => eight = Some(s),
-                _ => {}
-            }
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub struct SegmentSet(u8);
+
+impl SegmentSet {
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: SegmentSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersection(&self, other: SegmentSet) -> SegmentSet {
+        SegmentSet(self.0 & other.0)
+    }
+}
+
+impl FromStr for SegmentSet {
+    type Err = ParseEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseEntryError::EmptyPattern);
         }
 
-        let cf = one.unwrap();
-        let bd = four
-            .unwrap()
-            .to_owned()
-            .tap_mut(|s| s.retain(|ch| !cf.contains(ch)));
-        let a = seven
-            .unwrap()
-            .to_owned()
-            .tap_mut(|s| s.retain(|ch| !cf.contains(ch)))
-            .chars()
-            .next()
-            .unwrap();
-        let eg = eight
-            .unwrap()
-            .to_owned()
-            .tap_mut(|s| s.retain(|ch| !cf.contains(ch)))
-            .tap_mut(|s| s.retain(|ch| !bd.contains(ch)))
-            .tap_mut(|s| s.retain(|ch| ch != a));
-
-        let digits: u32 = output
-            .split(' ')
-            .map(|s| match s.len() {
-                2 => '1',
-                3 => '7',
-                4 => '4',
-                7 => '8',
-                _ => {
-                    let eg = has_eg(&eg, s);
-                    let cf = has_cf(cf, s);
-                    let a = has_a(&a.to_string(), s);
-                    let bd = has_bd(&bd, s);
-
-                    if eg && cf && a {
-                        '0'
-                    } else if a && cf && bd {
-                        '9'
-                    } else if a && cf {
-                        '3'
-                    } else if a && eg && bd {
-                        '6'
-                    } else if a && bd {
-                        '5'
-                    } else if a && eg {
-                        '2'
-                    } else {
-                        unreachable!();
-                    }
-                }
-            })
-            .collect::<String>()
-            .parse()
-            .unwrap();
-
-        result += digits;
-    }
-
-    result
+        s.chars().try_fold(SegmentSet::default(), |set, c| match c {
+            'a'..='g' => Ok(SegmentSet(set.0 | 1 << (c as u8 - b'a'))),
+            _ => Err(ParseEntryError::InvalidSegment(c)),
+        })
+    }
+}
+
+impl Display for SegmentSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let segments = ('a'..='g')
+            .enumerate()
+            .filter(|(i, _)| self.0 & 1 << i != 0)
+            .map(|(_, c)| c)
+            .collect::<String>();
+
+        write!(f, "{}", segments)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Entry {
+    pub patterns: [SegmentSet; 10],
+    pub outputs: [SegmentSet; 4],
 }
 
-fn has_eg(eg: &str, s: &str) -> bool {
-    eg.chars().all(|c| s.contains(c))
+impl Entry {
+    /// Works out which pattern belongs to which digit, indexed by the digit.
+    pub fn wiring(&self) -> Option<[SegmentSet; 10]> {
+        let with_len = |len| self.patterns.iter().filter(move |p| p.len() == len);
+
+        let one = *with_len(2).next()?;
+        let four = *with_len(4).next()?;
+        let seven = *with_len(3).next()?;
+        let eight = *with_len(7).next()?;
+
+        let mut digits = [SegmentSet::default(); 10];
+        digits[1] = one;
+        digits[4] = four;
+        digits[7] = seven;
+        digits[8] = eight;
+
+        for &p in with_len(6) {
+            let digit = if p.contains(four) {
+                9
+            } else if p.contains(one) {
+                0
+            } else {
+                6
+            };
+            digits[digit] = p;
+        }
+
+        for &p in with_len(5) {
+            let digit = if p.contains(one) {
+                3
+            } else if p.intersection(four).len() == 3 {
+                5
+            } else {
+                2
+            };
+            digits[digit] = p;
+        }
+
+        // Every digit has to be claimed by exactly one pattern
+        let mut claimed = digits.to_vec();
+        claimed.sort_unstable_by_key(|d| d.0);
+        claimed.dedup();
+        match claimed.len() == 10 && !claimed[0].is_empty() {
+            true => Some(digits),
+            false => None,
+        }
+    }
+
+    pub fn decode(&self) -> Option<u32> {
+        let digits = self.wiring()?;
+
+        self.outputs.iter().try_fold(0, |acc, o| {
+            let digit = digits.iter().position(|d| d == o)?;
+            Some(acc * 10 + digit as u32)
+        })
+    }
 }
 
-fn has_bd(bd: &str, s: &str) -> bool {
-    bd.chars().all(|c| s.contains(c))
+impl FromStr for Entry {
+    type Err = ParseEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (patterns, outputs) = s
+            .split_once(" | ")
+            .ok_or(ParseEntryError::MissingSeparator)?;
+
+        let patterns = patterns
+            .split_whitespace()
+            .map(|p| p.parse())
+            .collect::<Result<Vec<SegmentSet>, ParseEntryError>>()?;
+        let outputs = outputs
+            .split_whitespace()
+            .map(|o| o.parse())
+            .collect::<Result<Vec<SegmentSet>, ParseEntryError>>()?;
+
+        let patterns: [SegmentSet; 10] = patterns
+            .try_into()
+            .map_err(|p: Vec<SegmentSet>| ParseEntryError::PatternCount(p.len()))?;
+        let outputs: [SegmentSet; 4] = outputs
+            .try_into()
+            .map_err(|o: Vec<SegmentSet>| ParseEntryError::OutputCount(o.len()))?;
+
+        for (i, p) in patterns.iter().enumerate() {
+            if patterns[i + 1..].contains(p) {
+                return Err(ParseEntryError::DuplicatePattern(*p));
+            }
+        }
+
+        Ok(Self { patterns, outputs })
+    }
 }
-fn has_cf(cf: &str, s: &str) -> bool {
-    cf.chars().all(|c| s.contains(c))
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseEntryError {
+    MissingSeparator,
+    EmptyPattern,
+    InvalidSegment(char),
+    PatternCount(usize),
+    OutputCount(usize),
+    DuplicatePattern(SegmentSet),
 }
 
-fn has_a(a: &str, s: &str) -> bool {
-    s.contains(a)
+impl Display for ParseEntryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseEntryError::MissingSeparator => write!(f, "missing \" | \" separator"),
+            ParseEntryError::EmptyPattern => write!(f, "empty segment pattern"),
+            ParseEntryError::InvalidSegment(c) => write!(f, "invalid segment '{}'", c),
+            ParseEntryError::PatternCount(n) => write!(f, "expected 10 patterns, found {}", n),
+            ParseEntryError::OutputCount(n) => write!(f, "expected 4 outputs, found {}", n),
+            ParseEntryError::DuplicatePattern(p) => write!(f, "duplicate pattern '{}'", p),
+        }
+    }
 }
 
+impl std::error::Error for ParseEntryError {}
+
 #[cfg(test)]
 mod test_day08 {
-    use super::{solve_part1, solve_part2};
-
-    const INPUT: &str =
-        "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
-edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
-fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
-fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
-aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
-fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
-dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
-bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
-egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
-gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
+    use super::{
+        input_generator, solve_part1, solve_part2, Entry, ParseEntryError, EXAMPLE as INPUT,
+    };
 
     #[test]
     fn part1() {
-        assert_eq!(solve_part1(INPUT), 26);
+        let input = input_generator(INPUT);
+        assert_eq!(solve_part1(&input), 26);
     }
 
     #[test]
     fn part2() {
-        assert_eq!(solve_part2(INPUT), 61229);
+        let input = input_generator(INPUT);
+        assert_eq!(solve_part2(&input), 61229);
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let parse = |s: &str| s.parse::<Entry>();
+
+        assert_eq!(parse("ab cd | ef"), Err(ParseEntryError::PatternCount(2)));
+        assert_eq!(
+            parse("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edx | fdgacbe cefdb cefbgd gcbe"),
+            Err(ParseEntryError::InvalidSegment('x'))
+        );
+        assert!(matches!(
+            parse("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd eb | fdgacbe cefdb cefbgd gcbe"),
+            Err(ParseEntryError::DuplicatePattern(_))
+        ));
+        assert_eq!(
+            parse("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb"),
+            Err(ParseEntryError::MissingSeparator)
+        );
     }
 }
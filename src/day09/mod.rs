@@ -1,9 +1,19 @@
+pub mod naive;
+
+use crate::export::Grid;
+use crate::input::normalize;
+use crate::report::{self, Diagnostics};
+use crate::solution::Solution;
+use crate::synthetic;
 use itertools::Itertools;
-use std::collections::HashMap;
+use rand::Rng;
+use serde_json::json;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[aoc_generator(day09)]
 pub fn input_generator(input: &str) -> CaveSystem {
-    CaveSystem::new(input)
+    let input = normalize(input);
+    CaveSystem::new(&input)
 }
 
 #[aoc(day09, part1)]
@@ -26,6 +36,116 @@ pub fn solve_part2(input: &CaveSystem) -> i32 {
         .product()
 }
 
+pub const EXAMPLE: &str = "2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u32 = 9;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const FRAMES: bool = true;
+
+    type Input = CaveSystem;
+    type Output1 = i32;
+    type Output2 = i32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics(input: &Self::Input) -> Diagnostics {
+        let mut basins = input.find_basins();
+        basins.sort_unstable_by(|a, b| b.cmp(a));
+
+        report::diagnostics(json!({
+            "width": input.width,
+            "height": input.height,
+            "low_points": input.low_points().len(),
+            "basins": basins.len(),
+            "largest_basins": &basins[..basins.len().min(3)],
+        }))
+    }
+
+    fn frames(input: &Self::Input) -> Vec<(String, Grid)> {
+        vec![("heightmap".to_string(), input.to_grid())]
+    }
+
+    /// Square grid with roughly `scale` cells, split into basins around
+    /// random low points and walled off by 9s like the puzzle inputs. The
+    /// height rises with the distance from the low point, so every basin
+    /// has exactly one.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let side = synthetic::grid_side(scale);
+        let neighbours = |(x, y): (usize, usize)| {
+            [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .into_iter()
+                .map(move |(dx, dy)| (x as i64 + dx, y as i64 + dy))
+                .filter(|&(x, y)| x >= 0 && y >= 0 && x < side as i64 && y < side as i64)
+                .map(|(x, y)| (x as usize, y as usize))
+        };
+
+        // Assign every cell to its nearest low point
+        let low_points = (0..(side * side / 40).max(1))
+            .map(|_| (rng.gen_range(0..side), rng.gen_range(0..side)))
+            .collect::<Vec<(usize, usize)>>();
+        let mut basin = vec![vec![None; side]; side];
+        let mut queue = VecDeque::new();
+        for (i, &(x, y)) in low_points.iter().enumerate() {
+            if basin[y][x].is_none() {
+                basin[y][x] = Some(i);
+                queue.push_back((x, y));
+            }
+        }
+        while let Some(pos) = queue.pop_front() {
+            for (x, y) in neighbours(pos) {
+                if basin[y][x].is_none() {
+                    basin[y][x] = basin[pos.1][pos.0];
+                    queue.push_back((x, y));
+                }
+            }
+        }
+
+        // Wall off the basins, then let the height rise from each low point
+        let wall = |(x, y): (usize, usize)| {
+            neighbours((x, y)).any(|(nx, ny)| basin[ny][nx] != basin[y][x])
+        };
+        let mut heights = vec![vec![9; side]; side];
+        for (i, &(x, y)) in low_points.iter().enumerate() {
+            if basin[y][x] != Some(i) || wall((x, y)) {
+                continue;
+            }
+
+            heights[y][x] = rng.gen_range(0..=2);
+            queue.push_back((x, y));
+            while let Some(pos) = queue.pop_front() {
+                let height = (heights[pos.1][pos.0] + 1).min(8);
+                for (x, y) in neighbours(pos) {
+                    if heights[y][x] == 9 && basin[y][x] == Some(i) && !wall((x, y)) {
+                        heights[y][x] = height;
+                        queue.push_back((x, y));
+                    }
+                }
+            }
+        }
+
+        let rows = heights.iter().map(|row| itertools::join(row, ""));
+        Some(itertools::join(rows, "\n"))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CaveSystem {
     pub height_map: HashMap<(i32, i32), i32>,
@@ -59,6 +179,18 @@ impl CaveSystem {
         }
     }
 
+    /// Heights with the low points highlighted
+    pub fn to_grid(&self) -> Grid {
+        let mut grid = Grid::new(self.width as usize, self.height as usize, 9);
+        for (&(x, y), &height) in &self.height_map {
+            grid.set(x as usize, y as usize, height as u32);
+        }
+        for (x, y) in self.low_points() {
+            grid.highlight(x as usize, y as usize);
+        }
+        grid
+    }
+
     pub fn low_points(&self) -> Vec<(i32, i32)> {
         let mut result = vec![];
 
@@ -111,65 +243,34 @@ impl CaveSystem {
         true
     }
 
+    /// Size of the basin around every low point, that is the area around it
+    /// that is not walled off by 9s.
     pub fn find_basins(&self) -> Vec<i32> {
-        let low_points = self.low_points();
-        let mut basins = vec![];
-
-        for (x, y) in low_points {
-            let mut visited_points: Vec<(i32, i32)> = vec![];
-            let size = self.check_basin_neighbors(x, y, &mut visited_points);
-            basins.push(size);
-        }
-
-        basins
-    }
-
-    fn check_basin_neighbors(&self, x: i32, y: i32, visited: &mut Vec<(i32, i32)>) -> i32 {
-        // Check if already visited
-        if visited.contains(&(x, y)) {
-            return 0;
-        }
-        visited.push((x, y));
-
-        // Check out of bounds
-        if x < 0 || y < 0 || x >= self.width || y >= self.height {
-            return 0;
-        }
-
-        // Check if high_point
-        if let Some(height) = self.height_at(x, y) {
-            let height = height;
-            if *height == 9 {
-                return 0;
-            }
-        }
-
-        let mut res = 1;
-        // Check Top
-        res += self.check_basin_neighbors(x, y - 1, visited);
-
-        // Check Right
-        res += self.check_basin_neighbors(x + 1, y, visited);
-
-        // Check Bottom
-        res += self.check_basin_neighbors(x, y + 1, visited);
-
-        // Check Left
-        res += self.check_basin_neighbors(x - 1, y, visited);
+        self.low_points()
+            .into_iter()
+            .map(|low_point| {
+                let mut visited = HashSet::new();
+                let mut queue = vec![low_point];
+
+                while let Some((x, y)) = queue.pop() {
+                    if matches!(self.height_at(x, y), Some(height) if *height != 9)
+                        && visited.insert((x, y))
+                    {
+                        queue.extend([(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)]);
+                    }
+                }
 
-        res
+                visited.len() as i32
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod test_day09 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "2199943210
-3987894921
-9856789892
-8767896789
-9899965678";
+    use super::{input_generator, naive, solve_part1, solve_part2, Day09, EXAMPLE as INPUT};
+    use crate::solution::Solution;
+    use proptest::prelude::*;
 
     #[test]
     fn part1() {
@@ -182,4 +283,32 @@ mod test_day09 {
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 1134);
     }
+
+    #[test]
+    fn synthetic() {
+        let input = Day09::synthetic(1, 2500).unwrap();
+        let cave = input_generator(&input);
+
+        // Every cell that is not a 9 is in exactly one basin
+        let cells = input.chars().filter(|c| c.is_ascii_digit() && *c != '9');
+        assert_eq!(cave.find_basins().iter().sum::<i32>(), cells.count() as i32);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(rows in prop::collection::vec("[0-9]{1,12}", 1..12)) {
+            // Rows have to be equally long
+            let width = rows.iter().map(|r| r.len()).min().unwrap();
+            let grid = rows.iter().map(|r| &r[..width]).collect::<Vec<_>>().join("\n");
+            let cave = input_generator(&grid);
+
+            prop_assert_eq!(cave.find_basins(), naive::find_basins(&cave));
+        }
+
+        #[test]
+        fn matches_naive_synthetic(seed in any::<u64>(), scale in 1usize..1000) {
+            let cave = input_generator(&Day09::synthetic(seed, scale).unwrap());
+            prop_assert_eq!(cave.find_basins(), naive::find_basins(&cave));
+        }
+    }
 }
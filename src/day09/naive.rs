@@ -0,0 +1,52 @@
+//! Straightforward solution, flooding every basin recursively and keeping
+//! the visited points in a list.
+
+use super::CaveSystem;
+
+pub fn find_basins(cave: &CaveSystem) -> Vec<i32> {
+    let low_points = cave.low_points();
+    let mut basins = vec![];
+
+    for (x, y) in low_points {
+        let mut visited_points: Vec<(i32, i32)> = vec![];
+        let size = check_basin_neighbors(cave, x, y, &mut visited_points);
+        basins.push(size);
+    }
+
+    basins
+}
+
+fn check_basin_neighbors(cave: &CaveSystem, x: i32, y: i32, visited: &mut Vec<(i32, i32)>) -> i32 {
+    // Check if already visited
+    if visited.contains(&(x, y)) {
+        return 0;
+    }
+    visited.push((x, y));
+
+    // Check out of bounds
+    if x < 0 || y < 0 || x >= cave.width || y >= cave.height {
+        return 0;
+    }
+
+    // Check if high_point
+    if let Some(height) = cave.height_at(x, y) {
+        if *height == 9 {
+            return 0;
+        }
+    }
+
+    let mut res = 1;
+    // Check Top
+    res += check_basin_neighbors(cave, x, y - 1, visited);
+
+    // Check Right
+    res += check_basin_neighbors(cave, x + 1, y, visited);
+
+    // Check Bottom
+    res += check_basin_neighbors(cave, x, y + 1, visited);
+
+    // Check Left
+    res += check_basin_neighbors(cave, x - 1, y, visited);
+
+    res
+}
@@ -1,11 +1,15 @@
+use crate::input::normalize;
+use crate::report::{self, Diagnostics};
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::Rng;
+use serde_json::json;
 use std::collections::VecDeque;
 
 #[aoc_generator(day10)]
 pub fn input_generator(input: &str) -> Vec<Vec<Bracket>> {
-    input
-        .lines()
-        .map(|line| Bracket::from_sequence(line))
-        .collect()
+    let input = normalize(input);
+    input.lines().map(Bracket::from_sequence).collect()
 }
 
 #[aoc(day10, part1)]
@@ -42,8 +46,8 @@ pub fn solve_part1(input: &[Vec<Bracket>]) -> u64 {
 #[aoc(day10, part2)]
 pub fn solve_part2(input: &[Vec<Bracket>]) -> u64 {
     let mut scores = input
-        .to_vec()
-        .into_iter()
+        .iter()
+        .cloned()
         .filter_map(|line| {
             let mut open_stack: VecDeque<Bracket> = VecDeque::new();
             // Check line if corrupt
@@ -83,6 +87,91 @@ pub fn solve_part2(input: &[Vec<Bracket>]) -> u64 {
     *scores.get(scores.len() / 2).unwrap()
 }
 
+pub const EXAMPLE: &str = "[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]";
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u32 = 10;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = Vec<Vec<Bracket>>;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics(input: &Self::Input) -> Diagnostics {
+        let checked = input
+            .iter()
+            .map(|line| Bracket::check(line))
+            .collect::<Vec<_>>();
+        let corrupted = checked.iter().filter(|c| c.is_err()).count();
+        let incomplete = checked
+            .iter()
+            .filter(|c| matches!(c, Ok(open) if !open.is_empty()))
+            .count();
+
+        report::diagnostics(json!({
+            "lines": input.len(),
+            "corrupted_lines": corrupted,
+            "incomplete_lines": incomplete,
+        }))
+    }
+
+    /// Lines of up to 100 brackets, each either corrupted by a mismatching
+    /// closing bracket or left incomplete. The first line is always
+    /// incomplete, so part two has a score.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        const PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+        let mut rng = synthetic::rng(seed);
+
+        let lines = (0..scale.max(1)).map(|i| {
+            let (mut line, mut open) = (String::new(), vec![]);
+            for _ in 0..rng.gen_range(1..100) {
+                // Incomplete lines are scored in base 5, keep them short
+                // enough for the score to fit
+                if open.is_empty() || open.len() < 20 && rng.gen_bool(0.55) {
+                    let pair = rng.gen_range(0..PAIRS.len());
+                    line.push(PAIRS[pair].0);
+                    open.push(pair);
+                } else {
+                    line.push(PAIRS[open.pop().unwrap()].1);
+                }
+            }
+
+            match open.last() {
+                Some(&pair) if i > 0 && rng.gen_bool(0.5) => {
+                    line.push(PAIRS[(pair + rng.gen_range(1..PAIRS.len())) % PAIRS.len()].1)
+                }
+                Some(_) => {}
+                None => line.push(PAIRS[rng.gen_range(0..PAIRS.len())].0),
+            }
+            line
+        });
+        Some(itertools::join(lines, "\n"))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Bracket {
     // ()
@@ -128,6 +217,25 @@ impl Bracket {
         }
     }
 
+    /// Brackets left open at the end of the line, innermost first, or the
+    /// first closing bracket that does not match.
+    pub fn check(line: &[Bracket]) -> Result<Vec<&Bracket>, &Bracket> {
+        let mut open_stack = vec![];
+
+        for bracket in line {
+            if bracket.is_open() {
+                open_stack.push(bracket);
+            } else if open_stack.last().is_some_and(|open| bracket.cmp_type(open)) {
+                open_stack.pop();
+            } else {
+                return Err(bracket);
+            }
+        }
+
+        open_stack.reverse();
+        Ok(open_stack)
+    }
+
     pub fn cmp_type(&self, other: &Self) -> bool {
         matches!(
             (self, other),
@@ -163,18 +271,7 @@ pub enum OpenClose {
 
 #[cfg(test)]
 mod test_day10 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "[({(<(())[]>[[{[]{<()<>>
-[(()[<>])]({[<{<<[]>>(
-{([(<{}[<>[]}>{[]{[(<()>
-(((({<>}<{<{<>}{[]{[]{}
-[[<[([]))<([[{}[[()]]]
-[{[{({}]{}}([{[{{{}}([]
-{<[[]]>}<{[{[{[]{()[[[]
-[<(<(<(<{}))><([]([]()
-<{([([[(<>()){}]>(<<{{
-<{([{{}}[<[[[<>{}]]]>[]]";
+    use super::{input_generator, solve_part1, solve_part2, EXAMPLE as INPUT};
 
     #[test]
     fn part1() {
@@ -1,9 +1,19 @@
+use crate::animation::Recorder;
+use crate::export::Grid;
+use crate::input::normalize;
+use crate::report::{self, Diagnostics};
+use crate::simulation::Simulation;
+use crate::solution::Solution;
+use crate::synthetic;
 use colored::*;
+use rand::Rng;
+use serde_json::json;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 #[aoc_generator(day11)]
 pub fn input_generator(input: &str) -> OctopusMap {
+    let input = normalize(input);
     let mut map = HashMap::new();
 
     let tmp_map = input
@@ -58,55 +68,165 @@ pub fn solve_part2(input: &OctopusMap) -> u32 {
     step
 }
 
+pub const EXAMPLE: &str = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u32 = 11;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const FRAMES: bool = true;
+    const ANIMATED: bool = true;
+
+    type Input = OctopusMap;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics(input: &Self::Input) -> Diagnostics {
+        let (width, height) = input.size();
+        let mut map = input.clone();
+        let flashes = (0..100).map(|_| map.do_step()).collect::<Vec<u32>>();
+
+        report::diagnostics(json!({
+            "width": width,
+            "height": height,
+            "flashes_per_step": flashes,
+        }))
+    }
+
+    fn frames(input: &Self::Input) -> Vec<(String, Grid)> {
+        let mut map = input.clone();
+        let mut frames = vec![("step000".to_string(), map.to_grid())];
+        for step in 1..=100 {
+            map.do_step();
+            frames.push((format!("step{:03}", step), map.to_grid()));
+        }
+        frames
+    }
+
+    /// The first 100 steps, continued until the octopuses synchronise or
+    /// 1000 steps have passed.
+    fn animate(input: &Self::Input, recorder: &mut Recorder) {
+        let mut map = input.clone();
+        recorder.push(map.to_grid());
+        for step in 1..=1000 {
+            let flashes = map.do_step();
+            recorder.push(map.to_grid());
+            if step >= 100 && flashes as usize == map.map.len() {
+                break;
+            }
+        }
+    }
+
+    fn simulation(input: &Self::Input) -> Option<Box<dyn Simulation>> {
+        Some(Box::new(Flashes::new(input.clone())))
+    }
+
+    /// Square grid with roughly `scale` octopuses. Random grids hardly ever
+    /// synchronise, so all octopuses share one energy level except for some
+    /// isolated ones, which are low enough to never flash first and high
+    /// enough to be dragged along by their neighbours.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let side = synthetic::grid_side(scale);
+        let level = rng.gen_range(4..=9);
+
+        let rows = (0..side).map(|y| {
+            (0..side)
+                .map(|x| match x % 2 == 0 && y % 2 == 0 && rng.gen_bool(0.5) {
+                    true => rng.gen_range(level - 3..=level),
+                    false => level,
+                })
+                .map(|energy| char::from(b'0' + energy))
+                .collect::<String>()
+        });
+        Some(itertools::join(rows, "\n"))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OctopusMap {
     pub map: HashMap<(usize, usize), Octopus>,
 }
 
 impl OctopusMap {
+    /// Width and height of the map
+    pub fn size(&self) -> (usize, usize) {
+        self.map
+            .keys()
+            .fold((0, 0), |(w, h), &(x, y)| (w.max(x + 1), h.max(y + 1)))
+    }
+
+    /// Energy levels with the octopuses that flashed in the last step
+    /// highlighted
+    pub fn to_grid(&self) -> Grid {
+        let (width, height) = self.size();
+        let mut grid = Grid::new(width, height, 9);
+        for (&(x, y), octopus) in &self.map {
+            grid.set(x, y, octopus.energy);
+            if octopus.has_flashed {
+                grid.highlight(x, y);
+            }
+        }
+        grid
+    }
+
     pub fn do_step(&mut self) -> u32 {
         let mut flashes = 0;
 
         // Increase energy by one and reset has_flashed
-        self.map.iter_mut().for_each(|((_, _), octopus)| {
-            octopus.energy += 1;
-            octopus.has_flashed = false;
-        });
+        let mut flashing = self
+            .map
+            .iter_mut()
+            .filter_map(|(&pos, octopus)| {
+                octopus.energy += 1;
+                octopus.has_flashed = false;
+                (octopus.energy > 9).then_some(pos)
+            })
+            .collect::<Vec<(usize, usize)>>();
+
+        while let Some((x, y)) = flashing.pop() {
+            let octopus = self.map.get_mut(&(x, y)).unwrap();
+            if octopus.has_flashed {
+                continue;
+            }
 
-        let mut x: usize = 0;
-        let mut y: usize = 0;
-
-        'x: while x < 10 {
-            while y < 10 {
-                let octopus = self.map.get_mut(&(x, y)).unwrap();
-                if octopus.energy > 9 && !octopus.has_flashed {
-                    // Flash
-                    octopus.has_flashed = true;
-                    flashes += 1;
-
-                    for nx in -1..=1 {
-                        for ny in -1..=1 {
-                            let (nx, ny) = (x as i64 + nx, y as i64 + ny);
-                            // Check out of bounds
-                            if nx < 0 || ny < 0 || nx > 9 || ny > 9 {
-                                continue;
-                            }
-
-                            let neighbor = self.map.get_mut(&(nx as usize, ny as usize)).unwrap();
-                            neighbor.energy += 1;
+            // Flash
+            octopus.has_flashed = true;
+            flashes += 1;
+
+            for nx in x.saturating_sub(1)..=x + 1 {
+                for ny in y.saturating_sub(1)..=y + 1 {
+                    // Positions outside of the map are not in it
+                    if let Some(neighbor) = self.map.get_mut(&(nx, ny)) {
+                        neighbor.energy += 1;
+                        if neighbor.energy > 9 && !neighbor.has_flashed {
+                            flashing.push((nx, ny));
                         }
                     }
-
-                    // Start again
-                    x = 0;
-                    y = 0;
-                    continue 'x;
                 }
-
-                y += 1;
             }
-            y = 0;
-            x += 1;
         }
 
         // Reset has_flashed and energy levels
@@ -123,9 +243,10 @@ impl OctopusMap {
 impl Display for OctopusMap {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut res = String::new();
+        let (width, height) = self.size();
 
-        for y in 0..10 {
-            for x in 0..10 {
+        for y in 0..height {
+            for x in 0..width {
                 let octo = self.map.get(&(x, y)).unwrap();
 
                 match octo.has_flashed {
@@ -142,6 +263,62 @@ impl Display for OctopusMap {
     }
 }
 
+/// Steps of [`OctopusMap::do_step`], for the first 100 steps and then until
+/// all octopuses flash at once.
+#[derive(Debug, Clone)]
+pub struct Flashes {
+    pub map: OctopusMap,
+    pub step: u32,
+    pub last_flashes: u32,
+    pub total_flashes: u32,
+    pub synchronised: Option<u32>,
+}
+
+impl Flashes {
+    pub fn new(map: OctopusMap) -> Self {
+        Self {
+            map,
+            step: 0,
+            last_flashes: 0,
+            total_flashes: 0,
+            synchronised: None,
+        }
+    }
+}
+
+impl Simulation for Flashes {
+    fn step(&mut self) -> bool {
+        if self.step >= 100 && self.synchronised.is_some() {
+            return false;
+        }
+
+        self.step += 1;
+        self.last_flashes = self.map.do_step();
+        self.total_flashes += self.last_flashes;
+        if self.synchronised.is_none() && self.last_flashes as usize == self.map.map.len() {
+            self.synchronised = Some(self.step);
+        }
+        true
+    }
+
+    fn grid(&self) -> Grid {
+        self.map.to_grid()
+    }
+
+    fn stats(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("step", self.step.to_string()),
+            ("flashes", self.last_flashes.to_string()),
+            ("total flashes", self.total_flashes.to_string()),
+            (
+                "synchronised",
+                self.synchronised
+                    .map_or("not yet".to_string(), |step| format!("at step {}", step)),
+            ),
+        ]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Octopus {
     pub energy: u32,
@@ -159,18 +336,9 @@ impl Octopus {
 
 #[cfg(test)]
 mod test_day11 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "5483143223
-2745854711
-5264556173
-6141336146
-6357385478
-4167524645
-2176841721
-6882881134
-4846848554
-5283751526";
+    use super::{input_generator, solve_part1, solve_part2, Day11, EXAMPLE as INPUT};
+    use crate::animation::Recorder;
+    use crate::solution::Solution;
 
     #[test]
     fn part1() {
@@ -183,4 +351,25 @@ mod test_day11 {
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 195);
     }
+
+    #[test]
+    fn larger_grid() {
+        let input = input_generator(&Day11::synthetic(3, 30 * 20).unwrap());
+        assert_eq!(input.size(), (25, 25));
+
+        // The whole grid synchronises within the first ten steps
+        let step = solve_part2(&input);
+        assert!(step <= 10);
+        assert_eq!(solve_part1(&input) % 625, 0);
+    }
+
+    #[test]
+    fn animate() {
+        let mut recorder = Recorder::new();
+        Day11::animate(&input_generator(INPUT), &mut recorder);
+
+        // Initial state and every step up to the synchronisation
+        assert_eq!(recorder.len(), 196);
+        assert!(recorder.frames[195].highlighted.iter().all(|&h| h));
+    }
 }
@@ -1,7 +1,16 @@
+pub mod naive;
+
+use crate::input::normalize;
+use crate::report::{self, Diagnostics};
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::seq::SliceRandom;
+use serde_json::json;
 use std::collections::HashMap;
 
 #[aoc_generator(day12)]
 pub fn input_generator(input: &str) -> HashMap<String, Vec<String>> {
+    let input = normalize(input);
     let mut map: HashMap<String, Vec<String>> = HashMap::new();
 
     input.lines().for_each(|line| {
@@ -25,104 +34,191 @@ pub fn input_generator(input: &str) -> HashMap<String, Vec<String>> {
 
 #[aoc(day12, part1)]
 pub fn solve_part1(input: &HashMap<String, Vec<String>>) -> u32 {
-    let mut num_paths = 0;
-
-    visit_cave(
-        input,
-        "start".to_string(),
-        &mut vec![],
-        &mut num_paths,
-        false,
-    );
-
-    num_paths
+    count_paths(input, false)
 }
 
 #[aoc(day12, part2)]
 pub fn solve_part2(input: &HashMap<String, Vec<String>>) -> u32 {
-    let mut num_paths = 0;
+    count_paths(input, true)
+}
 
-    visit_cave(
-        input,
-        "start".to_string(),
-        &mut vec![],
-        &mut num_paths,
-        true,
-    );
+pub const EXAMPLE: &str = "start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end";
 
-    num_paths
-}
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u32 = 12;
+    const EXAMPLE: &'static str = EXAMPLE;
+
+    type Input = HashMap<String, Vec<String>>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
 
-fn visit_cave(
-    map: &HashMap<String, Vec<String>>,
-    pos: String,
-    path: &mut Vec<String>,
-    num_paths: &mut u32,
-    small_twice: bool,
-) {
-    if pos == "end" {
-        *num_paths += 1;
-        return;
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
     }
 
-    if let Some(next) = map.get(&pos) {
-        for next_path in next {
-            if next_path.eq("start") || is_path_invalid(next_path, path, small_twice) {
-                continue;
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics(input: &Self::Input) -> Diagnostics {
+        let small = input.keys().filter(|c| c.to_lowercase() == **c).count();
+        let connections = input.values().map(|n| n.len()).sum::<usize>() / 2;
+
+        report::diagnostics(json!({
+            "caves": input.len(),
+            "small_caves": small,
+            "big_caves": input.len() - small,
+            "connections": connections,
+        }))
+    }
+
+    /// Cave graph whose number of caves grows with the logarithm of `scale`,
+    /// as the number of paths grows exponentially with it. Big caves are
+    /// never connected to each other, so the number of paths stays finite.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let count = ((scale.max(1) as f64).log2() as usize).clamp(2, 8);
+        let name = |i: usize| {
+            let (a, b) = (
+                (b'a' + (i / 26) as u8) as char,
+                (b'a' + (i % 26) as u8) as char,
+            );
+            format!("{}{}", a, b)
+        };
+        let small = (0..count).map(name).collect::<Vec<String>>();
+        let big = (count..count + count / 3 + 1)
+            .map(|i| name(i).to_uppercase())
+            .collect::<Vec<String>>();
+
+        let mut caves = vec!["start".to_string(), "end".to_string()];
+        caves.extend(small.iter().cloned());
+        let mut edges = vec![];
+
+        // A chain through the small caves keeps the graph connected
+        for pair in caves.windows(2).skip(1) {
+            edges.push(format!("{}-{}", pair[1], pair[0]));
+        }
+        edges.push(format!("start-{}", small[0]));
+        for _ in 0..count / 2 {
+            let (a, b) = (
+                caves.choose(&mut rng).unwrap(),
+                small.choose(&mut rng).unwrap(),
+            );
+            if a != b {
+                edges.push(format!("{}-{}", a, b));
+            }
+        }
+        for cave in &big {
+            for other in caves.choose_multiple(&mut rng, 3) {
+                edges.push(format!("{}-{}", cave, other));
             }
-            path.push(next_path.clone());
-            visit_cave(map, next_path.clone(), path, num_paths, small_twice);
-            path.pop();
         }
+
+        edges.sort();
+        edges.dedup();
+        edges.shuffle(&mut rng);
+        Some(edges.join("\n"))
     }
 }
 
-#[allow(clippy::ptr_arg)]
-#[allow(clippy::if_same_then_else)]
-fn is_path_invalid(next_path: &String, path: &Vec<String>, small_twice: bool) -> bool {
-    let mut count: HashMap<&String, u32> = HashMap::new();
+/// Number of paths from `start` to `end` that visit small caves at most
+/// once, or a single one of them twice if `small_twice` is set. Paths are
+/// counted per cave and set of visited small caves, so each of those is only
+/// explored once.
+pub fn count_paths(map: &HashMap<String, Vec<String>>, small_twice: bool) -> u32 {
+    let names = map.keys().collect::<Vec<&String>>();
+    let index = |name: &str| names.iter().position(|n| *n == name);
 
-    for p in path {
-        *count.entry(p).or_insert(0) += 1;
-    }
-    *count.entry(next_path).or_insert(0) += 1;
+    // Small caves are numbered for the visited bitset, big ones need no bit
+    let mut small_caves = 0;
+    let small = names
+        .iter()
+        .map(|n| {
+            (n.to_lowercase() == **n).then(|| {
+                small_caves += 1;
+                small_caves - 1
+            })
+        })
+        .collect();
+
+    let mut caves = match (index("start"), index("end")) {
+        (Some(start), Some(end)) => Caves {
+            start,
+            end,
+            small,
+            neighbours: names
+                .iter()
+                .map(|n| map[*n].iter().filter_map(|c| index(c)).collect())
+                .collect(),
+            memo: HashMap::new(),
+        },
+        _ => return 0,
+    };
+
+    let visited = vec![0; small_caves.div_ceil(64)];
+    caves.count(caves.start, &visited, small_twice)
+}
+
+/// Caves by index, for [`count_paths`]
+struct Caves {
+    start: usize,
+    end: usize,
+    /// Bit of each small cave in the visited set
+    small: Vec<Option<usize>>,
+    neighbours: Vec<Vec<usize>>,
+    memo: HashMap<(usize, Vec<u64>, bool), u32>,
+}
 
-    let mut seen_twice = false;
-    for (pos, count) in count.iter() {
-        if !pos.to_lowercase().eq(*pos) {
-            continue;
+impl Caves {
+    fn count(&mut self, cave: usize, visited: &[u64], twice: bool) -> u32 {
+        if cave == self.end {
+            return 1;
+        }
+        if let Some(&paths) = self.memo.get(&(cave, visited.to_vec(), twice)) {
+            return paths;
         }
 
-        if small_twice {
-            if *count == 1 {
-                continue;
-            } else if *count == 2 && !seen_twice {
-                seen_twice = true;
-                continue;
-            } else if *count == 2 && seen_twice {
-                return true;
-            } else if *count > 2 {
-                return true;
-            }
-        } else if *count > 1 {
-            return true;
+        let mut paths = 0;
+        for i in 0..self.neighbours[cave].len() {
+            let next = self.neighbours[cave][i];
+
+            paths += match (next == self.start, self.small[next]) {
+                (true, _) => 0,
+                (_, None) => self.count(next, visited, twice),
+                (_, Some(bit)) if visited[bit / 64] & 1 << (bit % 64) == 0 => {
+                    let mut visited = visited.to_vec();
+                    visited[bit / 64] |= 1 << (bit % 64);
+                    self.count(next, &visited, twice)
+                }
+                (_, Some(_)) if twice => self.count(next, visited, false),
+                _ => 0,
+            };
         }
-    }
 
-    false
+        self.memo.insert((cave, visited.to_vec(), twice), paths);
+        paths
+    }
 }
 
 #[cfg(test)]
 mod test_day12 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "start-A
-start-b
-A-c
-A-b
-b-d
-A-end
-b-end";
+    use super::{
+        count_paths, input_generator, naive, solve_part1, solve_part2, Day12, EXAMPLE as INPUT,
+    };
+    use crate::solution::Solution;
+    use proptest::prelude::*;
 
     #[test]
     fn part1() {
@@ -135,4 +231,43 @@ b-end";
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 36);
     }
+
+    #[test]
+    fn larger_example() {
+        let input = input_generator(
+            "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sg\nkj-HN\nkj-dc",
+        );
+        assert_eq!(count_paths(&input, false), 19);
+        assert_eq!(count_paths(&input, true), 103);
+    }
+
+    #[test]
+    fn many_caves() {
+        // A chain of 70 small caves, with a big cave next to each of them
+        let mut edges = vec!["start-c0".to_string(), "c69-end".to_string()];
+        for i in 0..70 {
+            edges.push(format!("c{}-B{}", i, i));
+            if i > 0 {
+                edges.push(format!("c{}-c{}", i - 1, i));
+            }
+        }
+        let map = input_generator(&edges.join("\n"));
+
+        assert_eq!(count_paths(&map, false), 1);
+        assert_eq!(count_paths(&map, true), 71);
+        assert_eq!(count_paths(&map, true), naive::count_paths(&map, true));
+    }
+
+    proptest! {
+        // The naive solution takes a while on the larger graphs
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn matches_naive(seed in any::<u64>(), scale in 1usize..64) {
+            let map = input_generator(&Day12::synthetic(seed, scale).unwrap());
+
+            prop_assert_eq!(count_paths(&map, false), naive::count_paths(&map, false));
+            prop_assert_eq!(count_paths(&map, true), naive::count_paths(&map, true));
+        }
+    }
 }
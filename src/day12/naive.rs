@@ -0,0 +1,77 @@
+//! Straightforward solution, walking every path and checking the small
+//! caves on it before each step.
+
+use std::collections::HashMap;
+
+pub fn count_paths(map: &HashMap<String, Vec<String>>, small_twice: bool) -> u32 {
+    let mut num_paths = 0;
+
+    visit_cave(
+        map,
+        "start".to_string(),
+        &mut vec![],
+        &mut num_paths,
+        small_twice,
+    );
+
+    num_paths
+}
+
+fn visit_cave(
+    map: &HashMap<String, Vec<String>>,
+    pos: String,
+    path: &mut Vec<String>,
+    num_paths: &mut u32,
+    small_twice: bool,
+) {
+    if pos == "end" {
+        *num_paths += 1;
+        return;
+    }
+
+    if let Some(next) = map.get(&pos) {
+        for next_path in next {
+            if next_path.eq("start") || is_path_invalid(next_path, path, small_twice) {
+                continue;
+            }
+            path.push(next_path.clone());
+            visit_cave(map, next_path.clone(), path, num_paths, small_twice);
+            path.pop();
+        }
+    }
+}
+
+#[allow(clippy::ptr_arg)]
+#[allow(clippy::if_same_then_else)]
+fn is_path_invalid(next_path: &String, path: &Vec<String>, small_twice: bool) -> bool {
+    let mut count: HashMap<&String, u32> = HashMap::new();
+
+    for p in path {
+        *count.entry(p).or_insert(0) += 1;
+    }
+    *count.entry(next_path).or_insert(0) += 1;
+
+    let mut seen_twice = false;
+    for (pos, count) in count.iter() {
+        if !pos.to_lowercase().eq(*pos) {
+            continue;
+        }
+
+        if small_twice {
+            if *count == 1 {
+                continue;
+            } else if *count == 2 && !seen_twice {
+                seen_twice = true;
+                continue;
+            } else if *count == 2 && seen_twice {
+                return true;
+            } else if *count > 2 {
+                return true;
+            }
+        } else if *count > 1 {
+            return true;
+        }
+    }
+
+    false
+}
@@ -1,8 +1,17 @@
+use crate::export::Grid;
+use crate::input::{normalize, two_sections};
+use crate::report::{self, Diagnostics};
+use crate::simulation::Simulation;
+use crate::solution::Solution;
+use crate::synthetic;
+use rand::Rng;
+use serde_json::json;
 use std::collections::HashSet;
 
 #[aoc_generator(day13)]
 pub fn input_generator(input: &str) -> (Vec<Dot>, Vec<(char, u32)>) {
-    let (dots_str, folds_str) = input.split_once("\n\n").unwrap();
+    let input = normalize(input);
+    let (dots_str, folds_str) = two_sections(&input).unwrap();
 
     let mut dots = vec![];
     dots_str
@@ -14,7 +23,7 @@ pub fn input_generator(input: &str) -> (Vec<Dot>, Vec<(char, u32)>) {
         .for_each(|(x, y)| dots.push(Dot(x, y)));
 
     let mut folds = vec![];
-    let all_folds = folds_str.split('\n').collect::<Vec<&str>>();
+    let all_folds = folds_str.lines().collect::<Vec<&str>>();
     for all_fold in all_folds {
         let (axis, val) = all_fold
             .strip_prefix("fold along ")
@@ -61,6 +70,179 @@ pub fn solve_part2((dots, folds): &(Vec<Dot>, Vec<(char, u32)>)) -> String {
     Dot::print(&dots)
 }
 
+pub const EXAMPLE: &str = "6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5";
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u32 = 13;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const FRAMES: bool = true;
+
+    type Input = (Vec<Dot>, Vec<(char, u32)>);
+    type Output1 = usize;
+    type Output2 = String;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics((dots, folds): &Self::Input) -> Diagnostics {
+        let mut remaining = dots.iter().cloned().collect::<HashSet<Dot>>();
+        let mut dots_after_fold = vec![];
+        for (axis, value) in folds {
+            remaining = remaining.iter().map(|d| d.fold(*axis, *value)).collect();
+            dots_after_fold.push(remaining.len());
+        }
+
+        report::diagnostics(json!({
+            "dots": dots.len(),
+            "folds": folds.len(),
+            "dots_after_fold": dots_after_fold,
+        }))
+    }
+
+    fn frames((dots, folds): &Self::Input) -> Vec<(String, Grid)> {
+        let mut dots = dots.clone();
+        let mut frames = vec![("fold00".to_string(), Dot::to_grid(&dots))];
+        for (i, (axis, value)) in folds.iter().enumerate() {
+            dots = dots
+                .iter()
+                .map(|d| d.fold(*axis, *value))
+                .collect::<HashSet<Dot>>()
+                .into_iter()
+                .collect();
+            frames.push((format!("fold{:02}", i + 1), Dot::to_grid(&dots)));
+        }
+        frames
+    }
+
+    fn simulation((dots, folds): &Self::Input) -> Option<Box<dyn Simulation>> {
+        Some(Box::new(Folding {
+            dots: dots.clone(),
+            folds: folds.clone(),
+            done: 0,
+        }))
+    }
+
+    /// `scale` dots on a 40x6 code, unfolded six times along each axis.
+    /// Dots are mirrored at random while unfolding, so none lies on a fold.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let (mut width, mut height) = (40, 6);
+        let mut folds = vec![];
+        for _ in 0..6 {
+            folds.push(('x', width));
+            folds.push(('y', height));
+            width = 2 * width + 1;
+            height = 2 * height + 1;
+        }
+
+        let dots = (0..scale.max(1)).map(|_| {
+            let (mut x, mut y) = (rng.gen_range(0..40), rng.gen_range(0..6));
+            for &(axis, value) in &folds {
+                if rng.gen_bool(0.5) {
+                    match axis {
+                        'x' => x = 2 * value - x,
+                        _ => y = 2 * value - y,
+                    }
+                }
+            }
+            format!("{},{}", x, y)
+        });
+        let folds = folds
+            .iter()
+            .rev()
+            .map(|(axis, value)| format!("fold along {}={}", axis, value));
+
+        Some(format!(
+            "{}\n\n{}",
+            itertools::join(dots, "\n"),
+            itertools::join(folds, "\n")
+        ))
+    }
+}
+
+/// Paper folded one instruction at a time.
+#[derive(Debug, Clone)]
+pub struct Folding {
+    pub dots: Vec<Dot>,
+    pub folds: Vec<(char, u32)>,
+    /// Number of folds made so far
+    pub done: usize,
+}
+
+impl Simulation for Folding {
+    fn step(&mut self) -> bool {
+        let (axis, value) = match self.folds.get(self.done) {
+            Some(fold) => *fold,
+            None => return false,
+        };
+
+        self.dots = self
+            .dots
+            .iter()
+            .map(|d| d.fold(axis, value))
+            .collect::<HashSet<Dot>>()
+            .into_iter()
+            .collect();
+        self.done += 1;
+        true
+    }
+
+    fn grid(&self) -> Grid {
+        Dot::to_grid(&self.dots)
+    }
+
+    fn stats(&self) -> Vec<(&'static str, String)> {
+        let grid = self.grid();
+        let fold = |i: usize| {
+            self.folds.get(i).map_or("-".to_string(), |(axis, value)| {
+                format!("{}={}", axis, value)
+            })
+        };
+
+        vec![
+            ("folds", format!("{}/{}", self.done, self.folds.len())),
+            (
+                "last fold",
+                self.done.checked_sub(1).map_or("-".to_string(), fold),
+            ),
+            ("next fold", fold(self.done)),
+            ("visible dots", self.dots.len().to_string()),
+            ("paper", format!("{}x{}", grid.width, grid.height)),
+        ]
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Dot(u32, u32);
 
@@ -87,6 +269,18 @@ impl Dot {
         }
     }
 
+    /// Paper with a 1 for every dot
+    pub fn to_grid(dots: &[Dot]) -> Grid {
+        let width = dots.iter().map(|d| d.0 as usize + 1).max().unwrap_or(0);
+        let height = dots.iter().map(|d| d.1 as usize + 1).max().unwrap_or(0);
+
+        let mut grid = Grid::new(width, height, 1);
+        for dot in dots {
+            grid.set(dot.0 as usize, dot.1 as usize, 1);
+        }
+        grid
+    }
+
     pub fn print(dots: &[Dot]) -> String {
         let mut largest_x = 0;
         let mut largest_y = 0;
@@ -120,33 +314,30 @@ impl Dot {
 
 #[cfg(test)]
 mod test_day13 {
-    use super::{input_generator, solve_part1};
-
-    const INPUT: &str = "6,10
-0,14
-9,10
-0,3
-10,4
-4,11
-6,0
-6,12
-4,1
-0,13
-10,12
-3,4
-3,0
-8,4
-1,10
-2,14
-8,10
-9,0
-
-fold along y=7
-fold along x=5";
+    use super::{input_generator, solve_part1, Day13, EXAMPLE as INPUT};
+    use crate::solution::Solution;
 
     #[test]
     fn part1() {
         let input = input_generator(INPUT);
         assert_eq!(solve_part1(&input), 17);
     }
+
+    #[test]
+    fn trailing_newline() {
+        let input = input_generator(&format!("{}\r\n\r\n", INPUT.replace('\n', "\r\n")));
+        assert_eq!(input, input_generator(INPUT));
+        assert_eq!(input.1.len(), 2);
+    }
+
+    #[test]
+    fn frames() {
+        let frames = Day13::frames(&input_generator(INPUT));
+        assert_eq!(frames.len(), 3);
+
+        let (name, square) = &frames[2];
+        assert_eq!(name, "fold02");
+        assert_eq!((square.width, square.height), (5, 5));
+        assert_eq!(square.cells.iter().sum::<u32>(), 16);
+    }
 }
@@ -1,11 +1,22 @@
+pub mod naive;
+
+use crate::animation::Recorder;
+use crate::export::Grid;
+use crate::input::{normalize, two_sections};
+use crate::report::{self, Diagnostics};
+use crate::simulation::Simulation;
+use crate::solution::Solution;
+use crate::synthetic;
 use itertools::{Itertools, MinMaxResult};
+use rand::seq::SliceRandom;
+use serde_json::json;
 use std::collections::HashMap;
 use std::str::FromStr;
 
 #[aoc_generator(day14)]
 pub fn input_generator(input: &str) -> (Polymer, PairInsertions) {
-    input
-        .split_once("\n\n")
+    let input = normalize(input);
+    two_sections(&input)
         .map(|(poly, pairs)| {
             (
                 Polymer::from_str(poly).unwrap(),
@@ -43,6 +54,167 @@ pub fn solve_part2((polymer, pairs): &(Polymer, PairInsertions)) -> u64 {
     }
 }
 
+pub const EXAMPLE: &str = "NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C";
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u32 = 14;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const ANIMATED: bool = true;
+
+    type Input = (Polymer, PairInsertions);
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics((polymer, pairs): &Self::Input) -> Diagnostics {
+        let mut grown = polymer.clone();
+        for _ in 0..40 {
+            grown = pairs.apply(grown);
+        }
+
+        report::diagnostics(json!({
+            "template_length": polymer.counts.values().sum::<usize>(),
+            "rules": pairs.0.len(),
+            "elements": grown.counts.len(),
+            "length_after_40_steps": grown.counts.values().sum::<usize>(),
+        }))
+    }
+
+    /// Element counts for each of the 40 steps of part 2.
+    fn animate((polymer, pairs): &Self::Input, recorder: &mut Recorder) {
+        let elements = elements(polymer, pairs);
+        let mut polymer = polymer.clone();
+        recorder.push(polymer.to_grid(&elements));
+        for _ in 0..40 {
+            polymer = pairs.apply(polymer);
+            recorder.push(polymer.to_grid(&elements));
+        }
+    }
+
+    fn simulation((polymer, pairs): &Self::Input) -> Option<Box<dyn Simulation>> {
+        Some(Box::new(Growth {
+            elements: elements(polymer, pairs),
+            polymer: polymer.clone(),
+            pairs: pairs.clone(),
+            step: 0,
+        }))
+    }
+
+    /// Template of `scale` elements with a rule for every pair of ten random
+    /// elements.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let mut rng = synthetic::rng(seed);
+        let mut elements = ('A'..='Z').collect::<Vec<char>>();
+        elements.shuffle(&mut rng);
+        elements.truncate(10);
+
+        let template = (0..scale.clamp(2, 1_000_000))
+            .map(|_| elements.choose(&mut rng).unwrap())
+            .collect::<String>();
+        let rules = elements
+            .iter()
+            .cartesian_product(&elements)
+            .map(|(a, b)| format!("{}{} -> {}", a, b, elements.choose(&mut rng).unwrap()))
+            .collect::<Vec<String>>();
+
+        Some(format!("{}\n\n{}", template, rules.join("\n")))
+    }
+}
+
+/// Elements of the template and the rules, in alphabetical order. Charts use
+/// them so bars keep their position, even for elements which only appear
+/// after a few steps.
+fn elements(polymer: &Polymer, pairs: &PairInsertions) -> Vec<char> {
+    polymer
+        .counts
+        .keys()
+        .chain(pairs.0.values())
+        .copied()
+        .sorted()
+        .dedup()
+        .collect()
+}
+
+/// The 40 steps of part 2, one [`PairInsertions::apply`] each.
+#[derive(Debug, Clone)]
+pub struct Growth {
+    pub elements: Vec<char>,
+    pub polymer: Polymer,
+    pub pairs: PairInsertions,
+    pub step: usize,
+}
+
+impl Simulation for Growth {
+    fn step(&mut self) -> bool {
+        if self.step == 40 {
+            return false;
+        }
+
+        self.polymer = self.pairs.apply(self.polymer.clone());
+        self.step += 1;
+        true
+    }
+
+    fn grid(&self) -> Grid {
+        self.polymer.to_grid(&self.elements)
+    }
+
+    fn stats(&self) -> Vec<(&'static str, String)> {
+        let counts = &self.polymer.counts;
+        let common = |count: Option<(&char, &usize)>| {
+            count.map_or("-".to_string(), |(element, count)| {
+                format!("{} ({})", element, count)
+            })
+        };
+        let most = counts
+            .iter()
+            .max_by_key(|(&e, &c)| (c, std::cmp::Reverse(e)));
+        let least = counts.iter().min_by_key(|(&e, &c)| (c, e));
+
+        vec![
+            ("step", self.step.to_string()),
+            ("length", counts.values().sum::<usize>().to_string()),
+            ("elements", self.elements.iter().collect()),
+            ("most common", common(most)),
+            ("least common", common(least)),
+            (
+                "difference",
+                most.zip(least).map_or(0, |(m, l)| m.1 - l.1).to_string(),
+            ),
+        ]
+    }
+}
+
 pub type Pair = [char; 2];
 
 #[derive(Debug, Clone)]
@@ -51,6 +223,17 @@ pub struct Polymer {
     pub counts: HashMap<char, usize>,
 }
 
+impl Polymer {
+    /// Bar chart of the counts of `elements`, in the given order
+    pub fn to_grid(&self, elements: &[char]) -> Grid {
+        let counts = elements
+            .iter()
+            .map(|e| self.counts.get(e).copied().unwrap_or(0) as u128)
+            .collect::<Vec<u128>>();
+        Grid::bars(&counts, 32)
+    }
+}
+
 impl FromStr for Polymer {
     type Err = ();
 
@@ -69,6 +252,7 @@ impl FromStr for Polymer {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct PairInsertions(HashMap<Pair, char>);
 
 impl PairInsertions {
@@ -79,11 +263,15 @@ impl PairInsertions {
                 counts: polymer.counts,
             },
             |mut poly, (k, v)| {
-                if let Some(insertion) = self.0.get(&k) {
-                    *poly.counts.entry(*insertion).or_insert(0) += v;
-                    for pair in [[k[0], *insertion], [*insertion, k[1]]] {
-                        *poly.pairs.entry(pair).or_insert(0) += v;
+                match self.0.get(&k) {
+                    Some(insertion) => {
+                        *poly.counts.entry(*insertion).or_insert(0) += v;
+                        for pair in [[k[0], *insertion], [*insertion, k[1]]] {
+                            *poly.pairs.entry(pair).or_insert(0) += v;
+                        }
                     }
+                    // Pairs without a rule stay as they are
+                    None => *poly.pairs.entry(k).or_insert(0) += v,
                 }
                 poly
             },
@@ -116,26 +304,11 @@ impl FromStr for PairInsertions {
 
 #[cfg(test)]
 mod test_day14 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "NNCB
-
-CH -> B
-HH -> N
-CB -> H
-NH -> C
-HB -> C
-HC -> B
-HN -> C
-NN -> C
-BH -> H
-NC -> B
-NB -> B
-BN -> B
-BB -> N
-BC -> B
-CC -> N
-CN -> C";
+    use super::{
+        input_generator, naive, solve_part1, solve_part2, PairInsertions, Polymer, EXAMPLE as INPUT,
+    };
+    use proptest::prelude::*;
+    use std::collections::HashMap;
 
     #[test]
     fn part1() {
@@ -148,4 +321,43 @@ CN -> C";
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 2188189693529);
     }
+
+    #[test]
+    fn pairs_without_rule() {
+        let rules = PairInsertions(HashMap::from([(['N', 'N'], 'C')]));
+        let polymer = rules.apply("NNCB".parse::<Polymer>().unwrap());
+
+        // NNCB becomes NCNCB, the CB pair has no rule but is still there
+        assert_eq!(
+            polymer.pairs,
+            HashMap::from([(['N', 'C'], 2), (['C', 'N'], 1), (['C', 'B'], 1)])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive(
+            template in "[A-D]{1,8}",
+            insertions in prop::collection::vec(prop::option::of(prop::char::range('A', 'D')), 16),
+            steps in 0usize..8,
+        ) {
+            let elements = ['A', 'B', 'C', 'D'];
+            let rules = PairInsertions(
+                elements
+                    .iter()
+                    .flat_map(|&a| elements.iter().map(move |&b| [a, b]))
+                    .zip(insertions)
+                    .filter_map(|(pair, insertion)| Some((pair, insertion?)))
+                    .collect::<HashMap<_, _>>(),
+            );
+
+            let mut polymer = template.parse::<Polymer>().unwrap();
+            for _ in 0..steps {
+                polymer = rules.apply(polymer);
+            }
+
+            let expanded = naive::expand(&template, &rules, steps);
+            prop_assert_eq!(polymer.counts, naive::element_counts(&expanded));
+        }
+    }
 }
@@ -0,0 +1,29 @@
+//! Straightforward solution, building the whole polymer. Its length doubles
+//! with every step, so this only works for a few of them.
+
+use super::PairInsertions;
+use std::collections::HashMap;
+
+pub fn expand(template: &str, rules: &PairInsertions, steps: usize) -> String {
+    let mut polymer = template.chars().collect::<Vec<char>>();
+
+    for _ in 0..steps {
+        let mut next = vec![];
+        for (i, &element) in polymer.iter().enumerate() {
+            next.push(element);
+            if let Some(insertion) = polymer.get(i + 1).and_then(|&n| rules.0.get(&[element, n])) {
+                next.push(*insertion);
+            }
+        }
+        polymer = next;
+    }
+
+    polymer.into_iter().collect()
+}
+
+pub fn element_counts(polymer: &str) -> HashMap<char, usize> {
+    polymer.chars().fold(HashMap::new(), |mut counts, c| {
+        *counts.entry(c).or_insert(0) += 1;
+        counts
+    })
+}
@@ -1,73 +1,53 @@
-use pathfinding::prelude::dijkstra;
+use crate::export::Grid;
+use crate::input::normalize;
+use crate::report::{self, Diagnostics};
+use crate::solution::Solution;
+use crate::synthetic;
+use colored::*;
+use pathfinding::prelude::astar;
+use serde_json::json;
+use std::fmt::{Display, Formatter};
 
 const NEXT: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const NEXT_DIAGONAL: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
 
 #[aoc_generator(day15)]
-pub fn input_generator(input: &str) -> Vec<Vec<u8>> {
-    input
+pub fn input_generator(input: &str) -> TiledRiskMap {
+    let input = normalize(input);
+    let grid = input
         .lines()
         .map(|line| {
             line.chars()
                 .map(|c| c.to_string().parse().unwrap())
                 .collect::<Vec<u8>>()
         })
-        .collect()
+        .collect();
+
+    TiledRiskMap::new(grid, 1, 1).unwrap()
 }
 
 #[aoc(day15, part1)]
-pub fn solve_part1(input: &[Vec<u8>]) -> u32 {
-    dijkstra(
-        &(0, 0),
-        |(x, y)| {
-            NEXT.iter()
-                .map(|(nx, ny)| {
-                    input
-                        .get((y + ny) as usize)
-                        .and_then(|r| r.get((x + nx) as usize))
-                        .map(|c| ((x + nx, y + ny), *c as u32))
-                })
-                .flatten()
-                .collect::<Vec<_>>()
-        },
-        |&p| p == (input[0].len() as i32 - 1, input.len() as i32 - 1),
-    )
-    .unwrap()
-    .1
+pub fn solve_part1(input: &TiledRiskMap) -> u32 {
+    find_path(input, SearchOptions::default()).unwrap().total
 }
 
 #[aoc(day15, part2)]
-pub fn solve_part2(input: &[Vec<u8>]) -> u32 {
-    let dim = input.len();
-    let goal = (dim as i32 * 5 - 1, dim as i32 * 5 - 1);
+pub fn solve_part2(input: &TiledRiskMap) -> u32 {
+    let map = input.with_tiles(5, 5).materialise();
 
-    dijkstra(
-        &(0, 0),
-        |(x, y)| {
-            NEXT.iter()
-                .map(|&(nx, ny)| ((x + nx) as usize, (y + ny) as usize))
-                .filter(|(x, y)| (x / 5 < dim && y / 5 < dim))
-                .map(|(x, y)| {
-                    input.get(y % dim).and_then(|r| r.get(x % dim)).map(|c| {
-                        (
-                            (x as i32, y as i32),
-                            ((*c as usize + (x / dim) + (y / dim) - 1) % 9 + 1) as u32,
-                        )
-                    })
-                })
-                .flatten()
-                .collect::<Vec<_>>()
-        },
-        |&p| p == goal,
-    )
-    .unwrap()
-    .1
+    find_path(&map, SearchOptions::default()).unwrap().total
 }
 
-#[cfg(test)]
-mod test_day15 {
-    use super::{input_generator, solve_part1, solve_part2};
-
-    const INPUT: &str = "1163751742
+pub const EXAMPLE: &str = "1163751742
 1381373672
 2136511328
 3694931569
@@ -78,6 +58,294 @@ mod test_day15 {
 1293138521
 2311944581";
 
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u32 = 15;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const FRAMES: bool = true;
+
+    type Input = TiledRiskMap;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse(input: &str) -> Self::Input {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Output1 {
+        solve_part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Output2 {
+        solve_part2(input)
+    }
+
+    fn diagnostics(input: &Self::Input) -> Diagnostics {
+        let path = find_path(input, SearchOptions::default());
+
+        report::diagnostics(json!({
+            "width": input.width(),
+            "height": input.height(),
+            "path_length": path.map(|p| p.cells.len()),
+        }))
+    }
+
+    fn frames(input: &Self::Input) -> Vec<(String, Grid)> {
+        let tiled = input.with_tiles(5, 5).materialise();
+        [("path", input), ("path-tiled", &tiled)]
+            .into_iter()
+            .map(|(name, map)| {
+                let mut grid = Grid::from_rows(&map.rows(), 9);
+                if let Some(path) = find_path(map, SearchOptions::default()) {
+                    path.cells.iter().for_each(|&(x, y)| grid.highlight(x, y));
+                }
+                (name.to_string(), grid)
+            })
+            .collect()
+    }
+
+    /// Square grid of random digits with roughly `scale` cells.
+    fn synthetic(seed: u64, scale: usize) -> Option<String> {
+        let side = synthetic::grid_side(scale);
+        Some(synthetic::digit_grid(
+            &mut synthetic::rng(seed),
+            side,
+            side,
+            1..=9,
+        ))
+    }
+}
+
+/// Risk map repeated `tiles_x` times to the right and `tiles_y` times
+/// down. Risk increases by one for every tile away from the top left tile
+/// and wraps from 9 back to 1. Risks of repeated tiles are computed on
+/// access, `materialise` turns the map into a single dense tile.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TiledRiskMap {
+    base: Vec<Vec<u8>>,
+    base_width: usize,
+    base_height: usize,
+    pub tiles_x: usize,
+    pub tiles_y: usize,
+}
+
+impl TiledRiskMap {
+    pub fn new(base: Vec<Vec<u8>>, tiles_x: usize, tiles_y: usize) -> Result<Self, RiskMapError> {
+        let base_height = base.len();
+        let base_width = base.first().map(|r| r.len()).unwrap_or(0);
+
+        if base_width == 0 || tiles_x == 0 || tiles_y == 0 {
+            return Err(RiskMapError::Empty);
+        }
+        if let Some(y) = base.iter().position(|r| r.len() != base_width) {
+            return Err(RiskMapError::UnevenRow(y));
+        }
+
+        Ok(Self {
+            base,
+            base_width,
+            base_height,
+            tiles_x,
+            tiles_y,
+        })
+    }
+
+    /// Same base map with a different number of tiles.
+    pub fn with_tiles(&self, tiles_x: usize, tiles_y: usize) -> Self {
+        Self {
+            tiles_x: tiles_x.max(1),
+            tiles_y: tiles_y.max(1),
+            ..self.clone()
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.base_width * self.tiles_x
+    }
+
+    pub fn height(&self) -> usize {
+        self.base_height * self.tiles_y
+    }
+
+    pub fn risk(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+
+        let risk = self.base[y % self.base_height][x % self.base_width] as usize;
+        let increment = x / self.base_width + y / self.base_height;
+        // The base tile is kept as given. Repeated tiles add 8 rather than
+        // subtract 1 before wrapping, so a risk of 0 cannot underflow
+        Some(match increment {
+            0 => risk as u8,
+            _ => ((risk + increment + 8) % 9 + 1) as u8,
+        })
+    }
+
+    /// Lowest risk of any cell, including the repeated tiles.
+    pub fn min_risk(&self) -> u8 {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .map(|(x, y)| self.risk(x, y).unwrap())
+            .min()
+            .unwrap()
+    }
+
+    pub fn rows(&self) -> Vec<Vec<u8>> {
+        (0..self.height())
+            .map(|y| {
+                (0..self.width())
+                    .map(|x| self.risk(x, y).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn materialise(&self) -> Self {
+        Self {
+            base: self.rows(),
+            base_width: self.width(),
+            base_height: self.height(),
+            tiles_x: 1,
+            tiles_y: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RiskMapError {
+    Empty,
+    UnevenRow(usize),
+}
+
+impl Display for RiskMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskMapError::Empty => write!(f, "risk map is empty"),
+            RiskMapError::UnevenRow(y) => write!(f, "row {} differs in width", y),
+        }
+    }
+}
+
+impl std::error::Error for RiskMapError {}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SearchOptions {
+    pub diagonal: bool,
+    /// Use A* with a distance heuristic instead of plain Dijkstra
+    pub heuristic: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            diagonal: false,
+            heuristic: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RiskPath {
+    /// Visited cells as `(x, y)`, from the top left to the bottom right
+    pub cells: Vec<(usize, usize)>,
+    /// Risk of entering each cell, the start is never entered and counts 0
+    pub risks: Vec<u32>,
+    pub total: u32,
+}
+
+pub fn find_path(map: &TiledRiskMap, options: SearchOptions) -> Option<RiskPath> {
+    let goal = (map.width() as i32 - 1, map.height() as i32 - 1);
+
+    let moves: &[(i32, i32)] = match options.diagonal {
+        true => &NEXT_DIAGONAL,
+        false => &NEXT,
+    };
+    // Every step costs at least the lowest risk on the map, so the number of
+    // steps left times that risk is a lower bound on the remaining risk. Maps
+    // with a risk of 0 get no heuristic at all
+    let min_risk = match options.heuristic {
+        true => map.min_risk() as u32,
+        false => 0,
+    };
+    let heuristic = |&(x, y): &(i32, i32)| {
+        let (dx, dy) = ((goal.0 - x).unsigned_abs(), (goal.1 - y).unsigned_abs());
+        let steps = match options.diagonal {
+            false => dx + dy,
+            true => dx.max(dy),
+        };
+        steps * min_risk
+    };
+
+    let (cells, total) = astar(
+        &(0, 0),
+        |&(x, y)| {
+            moves.iter().filter_map(move |(nx, ny)| {
+                let (nx, ny) = (x + nx, y + ny);
+                if nx < 0 || ny < 0 {
+                    return None;
+                }
+                map.risk(nx as usize, ny as usize)
+                    .map(|risk| ((nx, ny), risk as u32))
+            })
+        },
+        heuristic,
+        |&p| p == goal,
+    )?;
+
+    let cells = cells
+        .into_iter()
+        .map(|(x, y)| (x as usize, y as usize))
+        .collect::<Vec<(usize, usize)>>();
+    let risks = cells
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| match i {
+            0 => 0,
+            _ => map.risk(x, y).unwrap() as u32,
+        })
+        .collect();
+
+    Some(RiskPath {
+        cells,
+        risks,
+        total,
+    })
+}
+
+/// Draws the map with the cells of `path` highlighted.
+pub fn render(map: &TiledRiskMap, path: &RiskPath) -> String {
+    let grid = map.rows();
+    let mut on_path = grid
+        .iter()
+        .map(|row| vec![false; row.len()])
+        .collect::<Vec<Vec<bool>>>();
+    for &(x, y) in &path.cells {
+        on_path[y][x] = true;
+    }
+
+    let mut res = String::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, risk) in row.iter().enumerate() {
+            match on_path[y][x] {
+                true => res.push_str(&risk.to_string().green().bold().to_string()),
+                false => res.push_str(&risk.to_string().dimmed().to_string()),
+            }
+        }
+        res.push('\n');
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod test_day15 {
+    use super::{
+        find_path, input_generator, render, solve_part1, solve_part2, RiskMapError, SearchOptions,
+        TiledRiskMap, EXAMPLE as INPUT,
+    };
+
     #[test]
     fn part1() {
         let input = input_generator(INPUT);
@@ -89,4 +357,94 @@ mod test_day15 {
         let input = input_generator(INPUT);
         assert_eq!(solve_part2(&input), 315);
     }
+
+    #[test]
+    fn path() {
+        let input = input_generator(INPUT);
+        let path = find_path(&input, SearchOptions::default()).unwrap();
+
+        assert_eq!(path.cells.first(), Some(&(0, 0)));
+        assert_eq!(path.cells.last(), Some(&(9, 9)));
+        assert_eq!(path.risks.iter().sum::<u32>(), path.total);
+
+        let dijkstra = SearchOptions {
+            heuristic: false,
+            ..SearchOptions::default()
+        };
+        assert_eq!(find_path(&input, dijkstra).unwrap().total, 40);
+
+        let diagonal = SearchOptions {
+            diagonal: true,
+            ..SearchOptions::default()
+        };
+        assert!(find_path(&input, diagonal).unwrap().total < 40);
+
+        let tiled = input.with_tiles(3, 3);
+        let path = find_path(&tiled, SearchOptions::default()).unwrap();
+        assert_eq!(path.cells.last(), Some(&(29, 29)));
+        assert_eq!(render(&tiled, &path).lines().count(), 30);
+    }
+
+    #[test]
+    fn rectangular_tiles() {
+        let base = vec![vec![8, 9, 1], vec![1, 2, 3]];
+        let map = TiledRiskMap::new(base, 2, 3).unwrap();
+
+        assert_eq!((map.width(), map.height()), (6, 6));
+        assert_eq!(map.rows()[0], vec![8, 9, 1, 9, 1, 2]);
+        assert_eq!(map.rows()[5], vec![3, 4, 5, 4, 5, 6]);
+        assert_eq!(map.risk(6, 0), None);
+        assert_eq!(map.materialise().rows(), map.rows());
+
+        let lazy = find_path(&map, SearchOptions::default()).unwrap();
+        let dense = find_path(&map.materialise(), SearchOptions::default()).unwrap();
+        assert_eq!(lazy.cells.last(), Some(&(5, 5)));
+        assert_eq!(lazy.total, dense.total);
+
+        assert_eq!(
+            TiledRiskMap::new(vec![vec![1, 2], vec![3]], 1, 1),
+            Err(RiskMapError::UnevenRow(1))
+        );
+    }
+
+    #[test]
+    fn zero_risk() {
+        let input = input_generator("10\n11");
+        assert_eq!(solve_part1(&input), 1);
+
+        let tiled = input.with_tiles(2, 1);
+        assert_eq!(tiled.rows()[0], vec![1, 0, 2, 1]);
+        assert_eq!(solve_part2(&input), 85);
+    }
+
+    #[test]
+    fn zero_risk_heuristic() {
+        let input = input_generator(
+            "00041
+10003
+41000
+60771
+80085
+00200",
+        );
+        assert_eq!(input.min_risk(), 0);
+
+        let dijkstra = SearchOptions {
+            heuristic: false,
+            ..SearchOptions::default()
+        };
+        assert_eq!(find_path(&input, dijkstra).unwrap().total, 3);
+        assert_eq!(
+            find_path(&input, SearchOptions::default()).unwrap().total,
+            3
+        );
+        assert_eq!(solve_part1(&input), 3);
+
+        let tiled = input.with_tiles(5, 5);
+        assert_eq!(tiled.min_risk(), 0);
+        assert_eq!(
+            find_path(&tiled, SearchOptions::default()).unwrap().total,
+            find_path(&tiled, dijkstra).unwrap().total
+        );
+    }
 }
@@ -0,0 +1,378 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    fn lerp(self, other: Rgb, t: f64) -> Rgb {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
+
+impl Display for Rgb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ColorMap {
+    Grayscale,
+    /// Black over red and yellow to white
+    Heat,
+    /// Purple over teal to yellow, like matplotlib's viridis
+    Viridis,
+}
+
+impl ColorMap {
+    fn stops(&self) -> &'static [Rgb] {
+        match self {
+            ColorMap::Grayscale => &[Rgb(0, 0, 0), Rgb(255, 255, 255)],
+            ColorMap::Heat => &[
+                Rgb(0, 0, 0),
+                Rgb(200, 30, 0),
+                Rgb(255, 210, 0),
+                Rgb(255, 255, 255),
+            ],
+            ColorMap::Viridis => &[
+                Rgb(68, 1, 84),
+                Rgb(59, 82, 139),
+                Rgb(33, 145, 140),
+                Rgb(94, 201, 98),
+                Rgb(253, 231, 37),
+            ],
+        }
+    }
+
+    /// Colour for `t` between 0 and 1, values outside are clamped.
+    pub fn color(&self, t: f64) -> Rgb {
+        let stops = self.stops();
+        let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let i = (position.floor() as usize).min(stops.len() - 2);
+
+        stops[i].lerp(stops[i + 1], position - i as f64)
+    }
+}
+
+impl FromStr for ColorMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gray" | "grayscale" => Ok(ColorMap::Grayscale),
+            "heat" => Ok(ColorMap::Heat),
+            "viridis" => Ok(ColorMap::Viridis),
+            _ => Err(format!("unknown colour map '{}'", s)),
+        }
+    }
+}
+
+/// Longest side of an image, in pixels, unless the grid has more cells.
+pub const MAX_IMAGE_SIZE: usize = 4096;
+
+/// How a [`Grid`] is drawn.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Style {
+    pub color_map: ColorMap,
+    /// Colour of highlighted cells, like a path or flashing octopuses
+    pub highlight: Rgb,
+    /// Side length of a cell in pixels. Large grids use smaller cells, so
+    /// images stay within [`MAX_IMAGE_SIZE`] pixels.
+    pub cell_size: usize,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            color_map: ColorMap::Viridis,
+            highlight: Rgb(255, 40, 40),
+            cell_size: 8,
+        }
+    }
+}
+
+/// Rectangular grid of values from 0 to `max`, with optionally highlighted
+/// cells.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    pub max: u32,
+    pub cells: Vec<u32>,
+    pub highlighted: Vec<bool>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize, max: u32) -> Self {
+        Self {
+            width,
+            height,
+            max,
+            cells: vec![0; width * height],
+            highlighted: vec![false; width * height],
+        }
+    }
+
+    pub fn from_rows<T: Copy + Into<u32>>(rows: &[Vec<T>], max: u32) -> Self {
+        let mut grid = Grid::new(rows.first().map_or(0, |r| r.len()), rows.len(), max);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                grid.set(x, y, (*value).into());
+            }
+        }
+        grid
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<u32> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+
+    /// Sets a cell, positions outside of the grid are ignored.
+    pub fn set(&mut self, x: usize, y: usize, value: u32) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = value;
+        }
+    }
+
+    pub fn highlight(&mut self, x: usize, y: usize) {
+        if x < self.width && y < self.height {
+            self.highlighted[y * self.width + x] = true;
+        }
+    }
+
+    /// Side length of a cell in pixels, see [`Style::cell_size`].
+    pub fn cell_size(&self, style: &Style) -> usize {
+        let cells = self.width.max(self.height).max(1);
+        style.cell_size.min(MAX_IMAGE_SIZE / cells).max(1)
+    }
+
+    pub fn color(&self, x: usize, y: usize, style: &Style) -> Rgb {
+        let i = y * self.width + x;
+        match self.highlighted[i] {
+            true => style.highlight,
+            false => style
+                .color_map
+                .color(self.cells[i] as f64 / self.max.max(1) as f64),
+        }
+    }
+
+    /// Bar chart with a column per value, scaled to the largest one. Bars
+    /// are coloured by their index and separated by empty columns.
+    pub fn bars(values: &[u128], height: usize) -> Self {
+        let mut grid = Grid::new(
+            (values.len() * 2).saturating_sub(1),
+            height,
+            values.len() as u32,
+        );
+        let largest = values.iter().max().copied().unwrap_or(0).max(1);
+
+        for (i, &value) in values.iter().enumerate() {
+            let bar = (value * height as u128).div_ceil(largest) as usize;
+            for y in height - bar..height {
+                grid.set(2 * i, y, i as u32 + 1);
+            }
+        }
+        grid
+    }
+
+    /// Width, height and the pixels of the image, row by row as RGB
+    /// triplets.
+    pub fn pixels(&self, style: &Style) -> (usize, usize, Vec<u8>) {
+        let size = self.cell_size(style);
+        let (width, height) = (self.width * size, self.height * size);
+
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let Rgb(r, g, b) = self.color(x / size, y / size, style);
+                pixels.extend([r, g, b]);
+            }
+        }
+
+        (width, height, pixels)
+    }
+
+    /// Binary PPM (P6) image.
+    pub fn to_ppm(&self, style: &Style) -> Vec<u8> {
+        let (width, height, pixels) = self.pixels(style);
+
+        let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        ppm.extend(pixels);
+        ppm
+    }
+
+    pub fn to_png(&self, style: &Style) -> Vec<u8> {
+        let (width, height, pixels) = self.pixels(style);
+
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+        png
+    }
+
+    /// SVG with one square per cell.
+    pub fn to_svg(&self, style: &Style) -> String {
+        let size = self.cell_size(style);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\">\n",
+            w = self.width * size,
+            h = self.height * size
+        );
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                svg += &format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"{}\"/>\n",
+                    x * size,
+                    y * size,
+                    self.color(x, y, style),
+                    s = size
+                );
+            }
+        }
+
+        svg + "</svg>\n"
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Format {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Png => "png",
+            Format::Svg => "svg",
+        }
+    }
+
+    pub fn render(&self, grid: &Grid, style: &Style) -> Vec<u8> {
+        match self {
+            Format::Ppm => grid.to_ppm(style),
+            Format::Png => grid.to_png(style),
+            Format::Svg => grid.to_svg(style).into_bytes(),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ppm" => Ok(Format::Ppm),
+            "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            _ => Err(format!("unknown format '{}'", s)),
+        }
+    }
+}
+
+/// Writes the grid to `path`, in the format given by its extension.
+pub fn write(grid: &Grid, style: &Style, path: &Path) -> Result<(), String> {
+    let format = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .ok_or(format!("{}: missing extension", path.display()))?
+        .parse::<Format>()?;
+
+    std::fs::write(path, format.render(grid, style))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod test_export {
+    use super::{ColorMap, Grid, Rgb, Style};
+
+    fn grid() -> Grid {
+        let mut grid = Grid::from_rows(&[vec![0u8, 9, 4], vec![9, 0, 9]], 9);
+        grid.highlight(1, 1);
+        grid
+    }
+
+    #[test]
+    fn color_map() {
+        assert_eq!(ColorMap::Grayscale.color(0.0), Rgb(0, 0, 0));
+        assert_eq!(ColorMap::Grayscale.color(1.0), Rgb(255, 255, 255));
+        assert_eq!(ColorMap::Grayscale.color(2.0), Rgb(255, 255, 255));
+        assert_eq!(ColorMap::Viridis.color(1.0), Rgb(253, 231, 37));
+        assert_eq!(ColorMap::Heat.color(0.5).to_string(), "#e47800");
+    }
+
+    #[test]
+    fn cell_size() {
+        let style = Style::default();
+        assert_eq!(grid().cell_size(&style), 8);
+        assert_eq!(Grid::new(1000, 10, 9).cell_size(&style), 4);
+        assert_eq!(Grid::new(10, 5000, 9).cell_size(&style), 1);
+    }
+
+    #[test]
+    fn bars() {
+        let bars = Grid::bars(&[4, 0, 1, 2], 4);
+        assert_eq!((bars.width, bars.height, bars.max), (7, 4, 4));
+
+        let heights = (0..bars.width)
+            .map(|x| {
+                (0..bars.height)
+                    .filter(|&y| bars.get(x, y) != Some(0))
+                    .count()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![4, 0, 0, 0, 1, 0, 2]);
+        assert_eq!(bars.get(6, 3), Some(4));
+    }
+
+    #[test]
+    fn ppm() {
+        let style = Style {
+            color_map: ColorMap::Grayscale,
+            cell_size: 2,
+            ..Style::default()
+        };
+        let ppm = grid().to_ppm(&style);
+        let header = b"P6\n6 4\n255\n";
+
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 6 * 4 * 3);
+        // Second pixel of the first row is still the first cell
+        assert_eq!(&ppm[header.len()..header.len() + 6], &[0, 0, 0, 0, 0, 0]);
+        assert_eq!(&ppm[header.len() + 6..header.len() + 9], &[255, 255, 255]);
+    }
+
+    #[test]
+    fn png() {
+        let png = grid().to_png(&Style::default());
+        let decoder = png::Decoder::new(png.as_slice());
+        let reader = decoder.read_info().unwrap();
+
+        assert_eq!(reader.info().width, 24);
+        assert_eq!(reader.info().height, 16);
+    }
+
+    #[test]
+    fn svg() {
+        let svg = grid().to_svg(&Style::default());
+
+        assert_eq!(svg.matches("<rect").count(), 6);
+        assert!(svg.contains("fill=\"#ff2828\""));
+        assert!(svg.contains("width=\"24\" height=\"16\""));
+    }
+}
@@ -0,0 +1,53 @@
+/// Input with `\n` line endings, without a byte order mark, trailing
+/// whitespace or trailing blank lines. Every generator starts with this, so
+/// files saved on Windows or with extra newlines parse like the originals.
+pub fn normalize(input: &str) -> String {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+
+    let mut lines = input.lines().map(str::trim_end).collect::<Vec<&str>>();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Blocks of lines of normalised input, separated by one or more blank
+/// lines.
+pub fn sections(input: &str) -> Vec<&str> {
+    input
+        .split("\n\n")
+        .map(|s| s.trim_matches('\n'))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// The two sections of inputs like `dots, folds` or `template, rules`.
+pub fn two_sections(input: &str) -> Option<(&str, &str)> {
+    match sections(input)[..] {
+        [first, second] => Some((first, second)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test_input {
+    use super::{normalize, sections, two_sections};
+
+    #[test]
+    fn normalize_input() {
+        assert_eq!(
+            normalize("\u{feff}1,2\r\n3 \r\n\r\n4\r\n\r\n\n"),
+            "1,2\n3\n\n4"
+        );
+        assert_eq!(normalize("a\nb"), "a\nb");
+        assert_eq!(normalize("\n\n"), "");
+    }
+
+    #[test]
+    fn split_sections() {
+        assert_eq!(sections("a\nb\n\nc\n\n\n\nd"), vec!["a\nb", "c", "d"]);
+        assert_eq!(sections(""), Vec::<&str>::new());
+        assert_eq!(two_sections("a\n\n\nb"), Some(("a", "b")));
+        assert_eq!(two_sections("a\n\nb\n\nc"), None);
+    }
+}
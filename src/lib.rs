@@ -1,21 +1,32 @@
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod animation;
+pub mod answers;
+pub mod bench;
+pub mod export;
+pub mod input;
+pub mod report;
+pub mod simulation;
+pub mod solution;
+pub mod synthetic;
+pub mod tui;
+
 // mod day00;
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
 
 aoc_lib! {year = 2021}
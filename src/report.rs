@@ -0,0 +1,121 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::answers::hash_input;
+use crate::input::{normalize, sections};
+
+/// Day specific statistics, see [`Solution::diagnostics`](crate::solution::Solution::diagnostics).
+pub type Diagnostics = serde_json::Map<String, serde_json::Value>;
+
+/// Diagnostics from a `json!` object.
+pub fn diagnostics(value: serde_json::Value) -> Diagnostics {
+    match value {
+        serde_json::Value::Object(map) => map,
+        _ => panic!("diagnostics have to be an object"),
+    }
+}
+
+/// Everything known about a day's run, to be serialised as JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub day: u32,
+    pub input: InputStats,
+    pub generator_nanos: u64,
+    pub parts: Vec<PartReport>,
+    pub diagnostics: Diagnostics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PartReport {
+    pub part: u32,
+    pub answer: String,
+    pub nanos: u64,
+}
+
+impl PartReport {
+    pub fn new(part: u32, answer: String, duration: Duration) -> Self {
+        Self {
+            part,
+            answer,
+            nanos: duration.as_nanos() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct InputStats {
+    pub bytes: usize,
+    pub lines: usize,
+    /// Blocks separated by blank lines
+    pub sections: usize,
+    pub longest_line: usize,
+    /// Hash as used by the answer store
+    pub hash: String,
+}
+
+impl InputStats {
+    /// Statistics of the normalised input, so line endings don't matter.
+    pub fn of(input: &str) -> Self {
+        let input = normalize(input);
+        Self {
+            bytes: input.len(),
+            lines: input.lines().count(),
+            sections: sections(&input).len(),
+            longest_line: input.lines().map(|l| l.len()).max().unwrap_or(0),
+            hash: format!("{:016x}", hash_input(&input)),
+        }
+    }
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test_report {
+    use super::{InputStats, PartReport, Report};
+    use crate::solution::find;
+    use std::time::Duration;
+
+    #[test]
+    fn input_stats() {
+        let stats = InputStats::of("NNCB\n\nCH -> B\nHH -> N");
+        assert_eq!(stats.bytes, 21);
+        assert_eq!(stats.lines, 4);
+        assert_eq!(stats.sections, 2);
+        assert_eq!(stats.longest_line, 7);
+
+        let windows = InputStats::of("NNCB\r\n\r\nCH -> B\r\nHH -> N\r\n");
+        assert_eq!(windows, stats);
+    }
+
+    #[test]
+    fn json() {
+        let day = find(10).unwrap();
+        let input = (day.parse)(day.example);
+        let report = Report {
+            day: 10,
+            input: InputStats::of(day.example),
+            generator_nanos: 5,
+            parts: vec![PartReport::new(
+                1,
+                "26397".to_string(),
+                Duration::from_nanos(7),
+            )],
+            diagnostics: (day.diagnostics)(input.as_ref()),
+        };
+
+        let json = serde_json::from_str::<serde_json::Value>(&report.to_json()).unwrap();
+        assert_eq!(json["day"], 10);
+        assert_eq!(json["parts"][0]["answer"], "26397");
+        assert_eq!(json["parts"][0]["nanos"], 7);
+        assert_eq!(json["diagnostics"]["corrupted_lines"], 5);
+        assert_eq!(json["diagnostics"]["incomplete_lines"], 5);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::export::Grid;
+
+/// A puzzle state that can be advanced one step at a time.
+pub trait Simulation {
+    /// Advances by one step, `false` if the simulation was already done.
+    fn step(&mut self) -> bool;
+
+    fn grid(&self) -> Grid;
+
+    /// Named statistics of the current state, like the step count.
+    fn stats(&self) -> Vec<(&'static str, String)>;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Snapshot {
+    pub grid: Grid,
+    pub stats: Vec<(&'static str, String)>,
+}
+
+impl Snapshot {
+    fn of(simulation: &dyn Simulation) -> Self {
+        Self {
+            grid: simulation.grid(),
+            stats: simulation.stats(),
+        }
+    }
+}
+
+/// Steps of a simulation, computed when first visited and kept so they can
+/// be stepped through backwards as well.
+pub struct Timeline {
+    simulation: Box<dyn Simulation>,
+    snapshots: Vec<Snapshot>,
+    position: usize,
+    finished: bool,
+}
+
+impl Timeline {
+    pub fn new(simulation: Box<dyn Simulation>) -> Self {
+        Self {
+            snapshots: vec![Snapshot::of(simulation.as_ref())],
+            simulation,
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Step currently shown, 0 being the initial state
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn current(&self) -> &Snapshot {
+        &self.snapshots[self.position]
+    }
+
+    /// Number of the last step, once the simulation is done
+    pub fn last(&self) -> Option<usize> {
+        self.finished.then(|| self.snapshots.len() - 1)
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.last() == Some(self.position)
+    }
+
+    /// Moves one step forward, `false` if there is none.
+    pub fn forward(&mut self) -> bool {
+        if self.position + 1 == self.snapshots.len() {
+            if self.finished || !self.simulation.step() {
+                self.finished = true;
+                return false;
+            }
+            self.snapshots.push(Snapshot::of(self.simulation.as_ref()));
+        }
+
+        self.position += 1;
+        true
+    }
+
+    pub fn back(&mut self) -> bool {
+        let moved = self.position > 0;
+        self.position = self.position.saturating_sub(1);
+        moved
+    }
+
+    /// Moves to `step`, or to the last step if the simulation ends before.
+    pub fn jump(&mut self, step: usize) -> usize {
+        self.position = step.min(self.position);
+        while self.position < step && self.forward() {}
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod test_simulation {
+    use super::{Simulation, Timeline};
+    use crate::export::Grid;
+
+    struct Countdown(u32);
+
+    impl Simulation for Countdown {
+        fn step(&mut self) -> bool {
+            let running = self.0 > 0;
+            self.0 = self.0.saturating_sub(1);
+            running
+        }
+
+        fn grid(&self) -> Grid {
+            Grid::bars(&[self.0 as u128], 4)
+        }
+
+        fn stats(&self) -> Vec<(&'static str, String)> {
+            vec![("left", self.0.to_string())]
+        }
+    }
+
+    #[test]
+    fn timeline() {
+        let mut timeline = Timeline::new(Box::new(Countdown(3)));
+        assert_eq!(timeline.current().stats, vec![("left", "3".to_string())]);
+        assert!(!timeline.back());
+
+        assert!(timeline.forward());
+        assert!(timeline.forward());
+        assert_eq!(timeline.last(), None);
+        assert!(timeline.back());
+        assert_eq!(timeline.position(), 1);
+        assert_eq!(timeline.current().stats[0].1, "2");
+
+        assert_eq!(timeline.jump(10), 3);
+        assert!(timeline.at_end());
+        assert!(!timeline.forward());
+        assert_eq!(timeline.jump(0), 0);
+        assert_eq!(timeline.current().stats[0].1, "3");
+    }
+}
@@ -0,0 +1,223 @@
+use std::any::Any;
+use std::fmt::Display;
+
+use crate::animation::Recorder;
+use crate::export::Grid;
+use crate::report::Diagnostics;
+use crate::simulation::Simulation;
+use crate::*;
+
+/// A day's puzzle: how to parse the input and solve both parts.
+pub trait Solution {
+    const DAY: u32;
+    const EXAMPLE: &'static str;
+    /// Whether the day overrides [`Solution::frames`], so days without
+    /// frames can be skipped before reading their input.
+    const FRAMES: bool = false;
+    /// Whether the day overrides [`Solution::animate`].
+    const ANIMATED: bool = false;
+
+    type Input: 'static;
+    type Output1: Display;
+    type Output2: Display;
+
+    fn parse(input: &str) -> Self::Input;
+
+    fn part1(input: &Self::Input) -> Self::Output1;
+
+    fn part2(input: &Self::Input) -> Self::Output2;
+
+    /// Random input that is valid for `parse`, growing with `scale`. The same
+    /// seed always produces the same input.
+    fn synthetic(_seed: u64, _scale: usize) -> Option<String> {
+        None
+    }
+
+    /// Day specific statistics about the input, like the number of basins,
+    /// included in JSON reports.
+    fn diagnostics(_input: &Self::Input) -> Diagnostics {
+        Diagnostics::new()
+    }
+
+    /// Named grids showing the puzzle state, for exporting as images.
+    fn frames(_input: &Self::Input) -> Vec<(String, Grid)> {
+        vec![]
+    }
+
+    /// Pushes the states of the simulation into `recorder`, for animating.
+    fn animate(_input: &Self::Input, _recorder: &mut Recorder) {}
+
+    /// Simulation to step through interactively, for the days that have one.
+    fn simulation(_input: &Self::Input) -> Option<Box<dyn Simulation>> {
+        None
+    }
+}
+
+/// Type-erased [`Solution`], so days with different input and output types
+/// can be listed together. Answers are rendered with `Display`.
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub day: u32,
+    pub example: &'static str,
+    pub has_frames: bool,
+    pub animated: bool,
+    pub synthetic: fn(u64, usize) -> Option<String>,
+    pub parse: fn(&str) -> Box<dyn Any>,
+    pub part1: fn(&dyn Any) -> String,
+    pub part2: fn(&dyn Any) -> String,
+    pub diagnostics: fn(&dyn Any) -> Diagnostics,
+    pub frames: fn(&dyn Any) -> Vec<(String, Grid)>,
+    pub animate: fn(&dyn Any, &mut Recorder),
+    pub simulation: fn(&dyn Any) -> Option<Box<dyn Simulation>>,
+}
+
+impl Day {
+    pub const fn of<S: Solution>() -> Self {
+        Self {
+            day: S::DAY,
+            example: S::EXAMPLE,
+            has_frames: S::FRAMES,
+            animated: S::ANIMATED,
+            synthetic: S::synthetic,
+            parse: parse::<S>,
+            part1: part1::<S>,
+            part2: part2::<S>,
+            diagnostics: diagnostics::<S>,
+            frames: frames::<S>,
+            animate: animate::<S>,
+            simulation: simulation::<S>,
+        }
+    }
+
+    pub fn solve(&self, part: u32, input: &dyn Any) -> Option<String> {
+        match part {
+            1 => Some((self.part1)(input)),
+            2 => Some((self.part2)(input)),
+            _ => None,
+        }
+    }
+}
+
+fn parse<S: Solution>(input: &str) -> Box<dyn Any> {
+    Box::new(S::parse(input))
+}
+
+fn part1<S: Solution>(input: &dyn Any) -> String {
+    S::part1(downcast::<S>(input)).to_string()
+}
+
+fn part2<S: Solution>(input: &dyn Any) -> String {
+    S::part2(downcast::<S>(input)).to_string()
+}
+
+fn diagnostics<S: Solution>(input: &dyn Any) -> Diagnostics {
+    S::diagnostics(downcast::<S>(input))
+}
+
+fn frames<S: Solution>(input: &dyn Any) -> Vec<(String, Grid)> {
+    S::frames(downcast::<S>(input))
+}
+
+fn animate<S: Solution>(input: &dyn Any, recorder: &mut Recorder) {
+    S::animate(downcast::<S>(input), recorder)
+}
+
+fn simulation<S: Solution>(input: &dyn Any) -> Option<Box<dyn Simulation>> {
+    S::simulation(downcast::<S>(input))
+}
+
+fn downcast<S: Solution>(input: &dyn Any) -> &S::Input {
+    input
+        .downcast_ref()
+        .unwrap_or_else(|| panic!("input was not parsed by day {}", S::DAY))
+}
+
+/// Every implemented day, in order.
+pub static DAYS: &[Day] = &[
+    Day::of::<day01::Day01>(),
+    Day::of::<day02::Day02>(),
+    Day::of::<day03::Day03>(),
+    Day::of::<day04::Day04>(),
+    Day::of::<day05::Day05>(),
+    Day::of::<day06::Day06>(),
+    Day::of::<day07::Day07>(),
+    Day::of::<day08::Day08>(),
+    Day::of::<day09::Day09>(),
+    Day::of::<day10::Day10>(),
+    Day::of::<day11::Day11>(),
+    Day::of::<day12::Day12>(),
+    Day::of::<day13::Day13>(),
+    Day::of::<day14::Day14>(),
+    Day::of::<day15::Day15>(),
+];
+
+pub fn find(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod test_solution {
+    use super::{find, DAYS};
+    use crate::animation::Recorder;
+
+    #[test]
+    fn registry() {
+        assert_eq!(
+            DAYS.iter().map(|d| d.day).collect::<Vec<_>>(),
+            (1..=15).collect::<Vec<_>>()
+        );
+
+        let day = find(7).unwrap();
+        let input = (day.parse)("16,1,2,0,4,2,7,1,2,14");
+        assert_eq!(day.solve(1, input.as_ref()).unwrap(), "37");
+        assert_eq!(day.solve(2, input.as_ref()).unwrap(), "168");
+        assert_eq!(day.solve(3, input.as_ref()), None);
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        for day in DAYS {
+            let example = (day.parse)(day.example);
+            let windows = format!("\u{feff}{}\r\n\r\n", day.example.replace('\n', "\r\n"));
+            let windows = (day.parse)(&windows);
+            for part in [1, 2] {
+                assert_eq!(
+                    day.solve(part, windows.as_ref()),
+                    day.solve(part, example.as_ref()),
+                    "day {} part {}",
+                    day.day,
+                    part
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn frame_support() {
+        for day in DAYS {
+            let example = (day.parse)(day.example);
+            assert_eq!(
+                !(day.frames)(example.as_ref()).is_empty(),
+                day.has_frames,
+                "day {}",
+                day.day
+            );
+
+            let mut recorder = Recorder::new();
+            (day.animate)(example.as_ref(), &mut recorder);
+            assert_eq!(!recorder.is_empty(), day.animated, "day {}", day.day);
+        }
+    }
+
+    #[test]
+    fn synthetic() {
+        for day in DAYS {
+            if let Some(input) = (day.synthetic)(7, 100) {
+                assert_eq!((day.synthetic)(7, 100).as_ref(), Some(&input));
+                let parsed = (day.parse)(&input);
+                assert!(day.solve(1, parsed.as_ref()).is_some());
+                assert!(day.solve(2, parsed.as_ref()).is_some());
+            }
+        }
+    }
+}
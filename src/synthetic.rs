@@ -0,0 +1,49 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::ops::RangeInclusive;
+
+/// Random number generator for synthetic inputs, so the same seed always
+/// produces the same input.
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Side length of a square grid with roughly `scale` cells.
+pub fn grid_side(scale: usize) -> usize {
+    ((scale as f64).sqrt().ceil() as usize).max(1)
+}
+
+/// Grid of random digits, one row per line.
+pub fn digit_grid(
+    rng: &mut StdRng,
+    width: usize,
+    height: usize,
+    digits: RangeInclusive<u8>,
+) -> String {
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| char::from(b'0' + rng.gen_range(digits.clone())))
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test_synthetic {
+    use super::{digit_grid, grid_side, rng};
+
+    #[test]
+    fn grid() {
+        assert_eq!(grid_side(10_000), 100);
+        assert_eq!(grid_side(101), 11);
+        assert_eq!(grid_side(0), 1);
+
+        let grid = digit_grid(&mut rng(1), 4, 3, 1..=9);
+        assert_eq!(grid.lines().count(), 3);
+        assert!(grid.lines().all(|l| l.len() == 4));
+        assert!(!grid.contains('0'));
+        assert_eq!(digit_grid(&mut rng(1), 4, 3, 1..=9), grid);
+    }
+}
@@ -0,0 +1,278 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::terminal;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style as TextStyle};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::export::{Grid, Rgb, Style};
+use crate::simulation::Timeline;
+
+const KEYS: [(&str, &str); 6] = [
+    ("→ l", "step forward"),
+    ("← h", "step back"),
+    ("space", "play/pause"),
+    ("+ -", "faster/slower"),
+    ("g", "jump to step"),
+    ("q", "quit"),
+];
+
+/// State of the stepping UI, independent of the terminal so it can be
+/// driven by key codes.
+pub struct App {
+    pub title: String,
+    pub timeline: Timeline,
+    pub style: Style,
+    pub playing: bool,
+    /// Time between steps while playing
+    pub delay: Duration,
+    /// Step typed after `g`, until enter is pressed
+    pub jump: Option<String>,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(title: String, timeline: Timeline, style: Style, delay: Duration) -> Self {
+        Self {
+            title,
+            timeline,
+            style,
+            playing: false,
+            delay,
+            jump: None,
+            quit: false,
+        }
+    }
+
+    pub fn handle(&mut self, key: KeyCode) {
+        if let Some(jump) = &mut self.jump {
+            match key {
+                KeyCode::Char(c) if c.is_ascii_digit() => jump.push(c),
+                KeyCode::Backspace => {
+                    jump.pop();
+                }
+                KeyCode::Enter => {
+                    if let Ok(step) = jump.parse() {
+                        self.timeline.jump(step);
+                    }
+                    self.jump = None;
+                }
+                KeyCode::Esc => self.jump = None,
+                _ => {}
+            }
+            return;
+        }
+
+        match key {
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.playing = false;
+                self.timeline.forward();
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.playing = false;
+                self.timeline.back();
+            }
+            KeyCode::Char(' ') => self.playing = !self.playing && !self.timeline.at_end(),
+            KeyCode::Char('+') => self.delay = (self.delay / 2).max(Duration::from_millis(10)),
+            KeyCode::Char('-') => self.delay = (self.delay * 2).min(Duration::from_secs(5)),
+            KeyCode::Char('g') => {
+                self.playing = false;
+                self.jump = Some(String::new());
+            }
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    /// Advances while playing, stopping at the end of the simulation.
+    pub fn tick(&mut self) {
+        if self.playing && !self.timeline.forward() {
+            self.playing = false;
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let areas =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(36)]).split(frame.area());
+
+        let snapshot = self.timeline.current();
+        let grid = Block::default().borders(Borders::ALL).title(format!(
+            " {} - step {}{} ",
+            self.title,
+            self.timeline.position(),
+            self.timeline
+                .last()
+                .map(|last| format!("/{}", last))
+                .unwrap_or_default()
+        ));
+        let inner = grid.inner(areas[0]);
+        frame.render_widget(grid, areas[0]);
+        frame.render_widget(
+            Paragraph::new(grid_lines(&snapshot.grid, &self.style, inner)),
+            inner,
+        );
+
+        let bold = TextStyle::default().add_modifier(Modifier::BOLD);
+        let mut lines = snapshot
+            .stats
+            .iter()
+            .map(|(name, value)| {
+                Line::from(vec![
+                    Span::styled(format!("{:<14}", name), bold),
+                    Span::raw(value.as_str()),
+                ])
+            })
+            .collect::<Vec<Line>>();
+
+        lines.push(Line::default());
+        lines.push(Line::from(match (&self.jump, self.playing) {
+            (Some(jump), _) => format!("jump to step: {}_", jump),
+            (None, true) => format!("playing, {:?} per step", self.delay),
+            (None, false) => "paused".to_string(),
+        }));
+        lines.push(Line::default());
+        lines.extend(KEYS.iter().map(|(keys, action)| {
+            Line::from(vec![
+                Span::styled(format!("{:<7}", keys), bold),
+                Span::raw(*action),
+            ])
+        }));
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" stats ")),
+            areas[1],
+        );
+    }
+}
+
+/// Rows of the grid that fit into `area`, with two characters per cell if
+/// there is room for it.
+fn grid_lines(grid: &Grid, style: &Style, area: Rect) -> Vec<Line<'static>> {
+    let area_width = area.width as usize;
+    let cell_width = if grid.width * 2 <= area_width { 2 } else { 1 };
+
+    (0..grid.height.min(area.height as usize))
+        .map(|y| {
+            (0..grid.width.min(area_width / cell_width))
+                .map(|x| {
+                    let Rgb(r, g, b) = grid.color(x, y, style);
+                    Span::styled(
+                        "█".repeat(cell_width),
+                        TextStyle::default().fg(Color::Rgb(r, g, b)),
+                    )
+                })
+                .collect::<Line>()
+        })
+        .collect()
+}
+
+/// Runs the UI on the terminal until the user quits. Fails if there is no
+/// terminal to run on.
+pub fn run(mut app: App) -> io::Result<()> {
+    // Setting up may fail halfway, with raw mode already enabled
+    let mut terminal = ratatui::try_init().inspect_err(|_| {
+        if terminal::is_raw_mode_enabled().unwrap_or(false) {
+            ratatui::restore();
+        }
+    })?;
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    let mut last_tick = Instant::now();
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if event::poll(app.delay.saturating_sub(last_tick.elapsed()))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle(key.code);
+                }
+            }
+        }
+
+        if last_tick.elapsed() >= app.delay {
+            app.tick();
+            last_tick = Instant::now();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_tui {
+    use super::App;
+    use crate::day11::{input_generator, Day11, EXAMPLE};
+    use crate::export::Style;
+    use crate::simulation::Timeline;
+    use crate::solution::Solution;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyCode;
+    use ratatui::Terminal;
+    use std::time::Duration;
+
+    fn app() -> App {
+        let simulation = Day11::simulation(&input_generator(EXAMPLE)).unwrap();
+        App::new(
+            "Day 11".to_string(),
+            Timeline::new(simulation),
+            Style::default(),
+            Duration::from_millis(100),
+        )
+    }
+
+    #[test]
+    fn keys() {
+        let mut app = app();
+        app.handle(KeyCode::Right);
+        app.handle(KeyCode::Char('l'));
+        app.handle(KeyCode::Left);
+        assert_eq!(app.timeline.position(), 1);
+
+        for key in [
+            KeyCode::Char('g'),
+            KeyCode::Char('1'),
+            KeyCode::Char('0'),
+            KeyCode::Enter,
+        ] {
+            app.handle(key);
+        }
+        assert_eq!(app.timeline.position(), 10);
+        assert_eq!(app.jump, None);
+
+        app.handle(KeyCode::Char(' '));
+        app.tick();
+        assert!(app.playing);
+        assert_eq!(app.timeline.position(), 11);
+
+        app.handle(KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn draw() {
+        let mut app = app();
+        app.timeline.jump(2);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(screen.contains("Day 11 - step 2"));
+        assert!(screen.contains("flashes"));
+        assert!(screen.contains("paused"));
+    }
+}